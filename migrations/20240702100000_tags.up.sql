@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS tags (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        name VARCHAR(50) NOT NULL,
+        color VARCHAR(7) NOT NULL DEFAULT '#9e9e9e',
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS tags_user_id_name_idx ON tags (user_id, LOWER(name));
+
+CREATE TABLE
+    IF NOT EXISTS list_tags (
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        PRIMARY KEY (list_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS list_tags_tag_id_idx ON list_tags (tag_id);
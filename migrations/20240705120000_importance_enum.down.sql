@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_user_id_importance_idx;
+
+ALTER TABLE lists ALTER COLUMN importance DROP NOT NULL;
+ALTER TABLE lists ALTER COLUMN importance DROP DEFAULT;
+ALTER TABLE lists ALTER COLUMN importance TYPE VARCHAR(15) USING importance::text;
+ALTER TABLE lists ALTER COLUMN importance SET DEFAULT 'medium';
+
+DROP TYPE IF EXISTS importance_level;
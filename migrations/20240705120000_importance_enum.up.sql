@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- Normalize free-form values before narrowing the column to the enum
+UPDATE lists SET importance = LOWER(TRIM(importance)) WHERE importance IS NOT NULL;
+UPDATE lists SET importance = 'high' WHERE importance IN ('urgent', 'critical', 'important', 'h');
+UPDATE lists SET importance = 'low' WHERE importance IN ('minor', 'trivial', 'l');
+UPDATE lists SET importance = 'medium' WHERE importance IS NULL OR importance NOT IN ('low', 'medium', 'high');
+
+-- Declared in ascending order so ORDER BY importance sorts by priority
+CREATE TYPE importance_level AS ENUM ('low', 'medium', 'high');
+
+ALTER TABLE lists ALTER COLUMN importance DROP DEFAULT;
+ALTER TABLE lists ALTER COLUMN importance TYPE importance_level USING importance::importance_level;
+ALTER TABLE lists ALTER COLUMN importance SET DEFAULT 'medium';
+ALTER TABLE lists ALTER COLUMN importance SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS lists_user_id_importance_idx ON lists (user_id, importance);
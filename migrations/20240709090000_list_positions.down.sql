@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_user_id_position_idx;
+ALTER TABLE lists DROP COLUMN IF EXISTS position;
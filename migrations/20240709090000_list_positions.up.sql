@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+ALTER TABLE lists ADD COLUMN IF NOT EXISTS position DOUBLE PRECISION;
+
+-- Seed manual order from creation order, leaving room between neighbours
+UPDATE lists l SET position = ordered.rn * 1024
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY created_at, id) AS rn FROM lists
+) ordered
+WHERE l.id = ordered.id;
+
+ALTER TABLE lists ALTER COLUMN position SET DEFAULT 0;
+ALTER TABLE lists ALTER COLUMN position SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS lists_user_id_position_idx ON lists (user_id, position);
@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_project_id_idx;
+DROP INDEX IF EXISTS lists_user_id_due_at_idx;
+
+ALTER TABLE lists
+    DROP COLUMN IF EXISTS project_id,
+    DROP COLUMN IF EXISTS completed_at,
+    DROP COLUMN IF EXISTS due_at,
+    DROP COLUMN IF EXISTS status;
+
+DROP TYPE IF EXISTS list_status;
+DROP TABLE IF EXISTS projects;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS projects (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        name VARCHAR(100) NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS projects_user_id_name_idx ON projects (user_id, LOWER(name));
+
+CREATE TYPE list_status AS ENUM ('todo', 'in_progress', 'done');
+
+ALTER TABLE lists
+    ADD COLUMN IF NOT EXISTS status list_status NOT NULL DEFAULT 'todo',
+    ADD COLUMN IF NOT EXISTS due_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS completed_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS project_id UUID REFERENCES projects(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS lists_user_id_due_at_idx ON lists (user_id, due_at);
+CREATE INDEX IF NOT EXISTS lists_project_id_idx ON lists (project_id);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_title_trgm_idx;
+DROP INDEX IF EXISTS lists_search_vector_idx;
+
+ALTER TABLE lists DROP COLUMN IF EXISTS search_vector;
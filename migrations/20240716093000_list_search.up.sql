@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE lists
+    ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', COALESCE(title, '')), 'A') ||
+        setweight(to_tsvector('english', COALESCE(descr, '')), 'B') ||
+        setweight(to_tsvector('english', COALESCE(body, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS lists_search_vector_idx ON lists USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS lists_title_trgm_idx ON lists USING GIN (title gin_trgm_ops);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_deleted_at_idx;
+
+ALTER TABLE lists DROP COLUMN IF EXISTS deleted_at;
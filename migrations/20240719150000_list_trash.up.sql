@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE lists ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS lists_deleted_at_idx ON lists (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_user_id_archived_at_idx;
+
+ALTER TABLE lists DROP COLUMN IF EXISTS archived_at;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE lists ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS lists_user_id_archived_at_idx ON lists (user_id, archived_at);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS lists_record_history ON lists;
+DROP FUNCTION IF EXISTS record_list_history();
+DROP TABLE IF EXISTS list_history;
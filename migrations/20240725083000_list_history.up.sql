@@ -0,0 +1,64 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS list_history (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+        action VARCHAR(20) NOT NULL,
+        changes JSONB NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX IF NOT EXISTS list_history_list_id_created_at_idx ON list_history (list_id, created_at);
+
+-- Every insert or update of a list item records the fields that changed as {"field": {"old": ..., "new": ...}}.
+-- The application sets app.actor_id (and app.history_action for reverts) for the current transaction.
+CREATE OR REPLACE FUNCTION record_list_history() RETURNS trigger AS $$
+DECLARE
+    tracked_fields TEXT[] := ARRAY['title', 'descr', 'body', 'importance', 'status', 'project_id', 'due_at', 'completed_at', 'archived_at', 'deleted_at'];
+    field TEXT;
+    old_row JSONB := '{}';
+    new_row JSONB := to_jsonb(NEW);
+    changes JSONB := '{}';
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        old_row := to_jsonb(OLD);
+    END IF;
+
+    FOREACH field IN ARRAY tracked_fields LOOP
+        IF (old_row -> field) IS DISTINCT FROM (new_row -> field) AND NOT (TG_OP = 'INSERT' AND new_row -> field = 'null') THEN
+            changes := changes || jsonb_build_object(field, jsonb_build_object('old', old_row -> field, 'new', new_row -> field));
+        END IF;
+    END LOOP;
+
+    IF changes <> '{}' THEN
+        INSERT INTO list_history (list_id, actor_id, action, changes)
+        VALUES (
+            NEW.id,
+            NULLIF(current_setting('app.actor_id', true), '')::uuid,
+            CASE WHEN TG_OP = 'INSERT' THEN 'created' ELSE COALESCE(NULLIF(current_setting('app.history_action', true), ''), 'updated') END,
+            changes
+        );
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_record_history
+    AFTER INSERT OR UPDATE ON lists
+    FOR EACH ROW EXECUTE FUNCTION record_list_history();
+
+-- Existing items start their history from their current state
+INSERT INTO list_history (list_id, actor_id, action, changes, created_at)
+SELECT
+    l.id,
+    NULL,
+    'created',
+    (SELECT COALESCE(jsonb_object_agg(f.key, jsonb_build_object('old', NULL, 'new', f.value)), '{}')
+        FROM jsonb_each(to_jsonb(l)) f
+        WHERE f.key IN ('title', 'descr', 'body', 'importance', 'status', 'project_id', 'due_at', 'completed_at', 'archived_at', 'deleted_at')
+        AND f.value <> 'null'),
+    COALESCE(l.created_at, NOW())
+FROM lists l;
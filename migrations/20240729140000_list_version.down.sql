@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS lists_bump_version ON lists;
+DROP FUNCTION IF EXISTS bump_list_version();
+
+ALTER TABLE lists DROP COLUMN IF EXISTS version;
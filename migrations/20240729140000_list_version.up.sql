@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+ALTER TABLE lists ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+-- Every write bumps the version, whichever code path it comes from, so ETags never go stale unnoticed
+CREATE OR REPLACE FUNCTION bump_list_version() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_bump_version
+    BEFORE UPDATE ON lists
+    FOR EACH ROW EXECUTE FUNCTION bump_list_version();
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_user_id_title_idx;
+DROP TABLE IF EXISTS idempotency_keys;
@@ -0,0 +1,48 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS idempotency_keys (
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        key VARCHAR(255) NOT NULL,
+        request_hash BYTEA NOT NULL,
+        -- Left empty while the first request is still running
+        status_code SMALLINT,
+        response_body BYTEA,
+        content_type VARCHAR(255),
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (user_id, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at_idx ON idempotency_keys (created_at);
+
+-- Titles are unique per user among items that aren't in the trash; rename existing duplicates
+-- first, skipping numbered titles the user already has
+DO $$
+DECLARE
+    duplicate RECORD;
+    suffix TEXT;
+    candidate TEXT;
+    n INT;
+BEGIN
+    FOR duplicate IN
+        SELECT id, user_id, title FROM (
+            SELECT id, user_id, title, ROW_NUMBER() OVER (PARTITION BY user_id, title ORDER BY created_at, id) AS rn
+            FROM lists WHERE deleted_at IS NULL
+        ) ranked
+        WHERE rn > 1
+    LOOP
+        n := 2;
+        LOOP
+            suffix := ' (' || n || ')';
+            -- Titles are still VARCHAR(50) at this point
+            candidate := LEFT(duplicate.title, 50 - LENGTH(suffix)) || suffix;
+            EXIT WHEN NOT EXISTS (
+                SELECT 1 FROM lists WHERE user_id = duplicate.user_id AND title = candidate AND deleted_at IS NULL
+            );
+            n := n + 1;
+        END LOOP;
+        UPDATE lists SET title = candidate WHERE id = duplicate.id;
+    END LOOP;
+END $$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS lists_user_id_title_idx ON lists (user_id, title) WHERE deleted_at IS NULL;
@@ -0,0 +1,23 @@
+-- Add down migration script here
+ALTER TABLE lists
+    DROP CONSTRAINT IF EXISTS lists_title_length,
+    DROP CONSTRAINT IF EXISTS lists_descr_length,
+    DROP CONSTRAINT IF EXISTS lists_body_length;
+
+DROP INDEX IF EXISTS lists_search_vector_idx;
+ALTER TABLE lists DROP COLUMN IF EXISTS search_vector;
+
+-- Longer values are cut off
+ALTER TABLE lists
+    ALTER COLUMN title TYPE VARCHAR(50) USING LEFT(title, 50),
+    ALTER COLUMN descr TYPE VARCHAR(50) USING LEFT(descr, 50),
+    ALTER COLUMN body TYPE VARCHAR(50) USING LEFT(body, 50);
+
+ALTER TABLE lists
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', COALESCE(title, '')), 'A') ||
+        setweight(to_tsvector('english', COALESCE(descr, '')), 'B') ||
+        setweight(to_tsvector('english', COALESCE(body, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS lists_search_vector_idx ON lists USING GIN (search_vector);
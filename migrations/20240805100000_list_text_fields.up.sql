@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- search_vector is generated from these columns, so it has to be rebuilt around the type change
+DROP INDEX IF EXISTS lists_search_vector_idx;
+ALTER TABLE lists DROP COLUMN IF EXISTS search_vector;
+
+ALTER TABLE lists
+    ALTER COLUMN title TYPE TEXT,
+    ALTER COLUMN descr TYPE TEXT,
+    ALTER COLUMN body TYPE TEXT;
+
+ALTER TABLE lists
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', COALESCE(title, '')), 'A') ||
+        setweight(to_tsvector('english', COALESCE(descr, '')), 'B') ||
+        setweight(to_tsvector('english', COALESCE(body, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS lists_search_vector_idx ON lists USING GIN (search_vector);
+
+-- Hard ceilings; the API enforces the (lower) limits from the configuration
+ALTER TABLE lists
+    ADD CONSTRAINT lists_title_length CHECK (char_length(title) <= 1000),
+    ADD CONSTRAINT lists_descr_length CHECK (char_length(descr) <= 10000),
+    ADD CONSTRAINT lists_body_length CHECK (char_length(body) <= 500000);
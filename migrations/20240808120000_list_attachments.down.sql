@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS attachments_queue_orphaned_file ON attachments;
+DROP FUNCTION IF EXISTS queue_orphaned_file();
+DROP TABLE IF EXISTS orphaned_files;
+DROP TABLE IF EXISTS attachments;
@@ -0,0 +1,33 @@
+-- Add up migration script here
+CREATE TABLE
+    IF NOT EXISTS attachments (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+        filename TEXT NOT NULL,
+        content_type VARCHAR(255) NOT NULL,
+        size_bytes BIGINT NOT NULL,
+        storage_key TEXT NOT NULL UNIQUE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS attachments_list_id_idx ON attachments (list_id, created_at);
+
+-- Files whose attachment row is gone (deleted directly or along with its list item) and that
+-- still have to be removed from storage
+CREATE TABLE
+    IF NOT EXISTS orphaned_files (
+        storage_key TEXT PRIMARY KEY NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION queue_orphaned_file() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO orphaned_files (storage_key) VALUES (OLD.storage_key) ON CONFLICT DO NOTHING;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER attachments_queue_orphaned_file
+    AFTER DELETE ON attachments
+    FOR EACH ROW EXECUTE FUNCTION queue_orphaned_file();
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP FUNCTION IF EXISTS list_role(UUID, UUID);
+DROP FUNCTION IF EXISTS project_role(UUID, UUID);
+DROP TABLE IF EXISTS project_shares;
+DROP TABLE IF EXISTS list_shares;
+DROP TYPE IF EXISTS share_role;
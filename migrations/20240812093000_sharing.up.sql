@@ -0,0 +1,50 @@
+-- Add up migration script here
+CREATE TYPE share_role AS ENUM ('viewer', 'editor', 'owner');
+
+CREATE TABLE
+    IF NOT EXISTS list_shares (
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        role share_role NOT NULL,
+        shared_by UUID REFERENCES users(id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (list_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS list_shares_user_id_idx ON list_shares (user_id);
+
+CREATE TABLE
+    IF NOT EXISTS project_shares (
+        project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        role share_role NOT NULL,
+        shared_by UUID REFERENCES users(id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (project_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS project_shares_user_id_idx ON project_shares (user_id);
+
+-- What a user may do with a project: 'owner' for its creator, otherwise whatever it was shared as
+CREATE OR REPLACE FUNCTION project_role(p_project_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT CASE
+        WHEN p.user_id = p_user_id THEN 'owner'::share_role
+        ELSE (SELECT s.role FROM project_shares s WHERE s.project_id = p.id AND s.user_id = p_user_id)
+    END
+    FROM projects p
+    WHERE p.id = p_project_id
+$$ LANGUAGE sql STABLE;
+
+-- What a user may do with a list item: the highest of owning it, its project role and the role
+-- it was shared with them as; NULL when they have no access at all
+CREATE OR REPLACE FUNCTION list_role(p_list_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT CASE
+        WHEN l.user_id = p_user_id THEN 'owner'::share_role
+        ELSE GREATEST(
+            (SELECT s.role FROM list_shares s WHERE s.list_id = l.id AND s.user_id = p_user_id),
+            project_role(l.project_id, p_user_id)
+        )
+    END
+    FROM lists l
+    WHERE l.id = p_list_id
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,33 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION project_role(p_project_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT CASE
+        WHEN p.user_id = p_user_id THEN 'owner'::share_role
+        ELSE (SELECT s.role FROM project_shares s WHERE s.project_id = p.id AND s.user_id = p_user_id)
+    END
+    FROM projects p
+    WHERE p.id = p_project_id
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION list_role(p_list_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT CASE
+        WHEN l.user_id = p_user_id THEN 'owner'::share_role
+        ELSE GREATEST(
+            (SELECT s.role FROM list_shares s WHERE s.list_id = l.id AND s.user_id = p_user_id),
+            project_role(l.project_id, p_user_id)
+        )
+    END
+    FROM lists l
+    WHERE l.id = p_list_id
+$$ LANGUAGE sql STABLE;
+
+DROP FUNCTION IF EXISTS workspace_share_role(UUID, UUID);
+DROP TRIGGER IF EXISTS lists_project_workspace ON lists;
+DROP FUNCTION IF EXISTS check_list_project_workspace();
+ALTER TABLE lists DROP COLUMN IF EXISTS workspace_id;
+ALTER TABLE projects DROP COLUMN IF EXISTS workspace_id;
+DROP TRIGGER IF EXISTS users_personal_workspace ON users;
+DROP FUNCTION IF EXISTS create_personal_workspace();
+DROP TABLE IF EXISTS workspace_invitations;
+DROP TABLE IF EXISTS workspace_members;
+DROP TABLE IF EXISTS workspaces;
+DROP TYPE IF EXISTS workspace_role;
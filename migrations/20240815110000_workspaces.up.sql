@@ -0,0 +1,127 @@
+-- Add up migration script here
+CREATE TYPE workspace_role AS ENUM ('guest', 'member', 'admin', 'owner');
+
+CREATE TABLE
+    IF NOT EXISTS workspaces (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        name VARCHAR(100) NOT NULL,
+        personal BOOLEAN NOT NULL DEFAULT FALSE,
+        created_by UUID REFERENCES users(id) ON DELETE CASCADE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Every user has exactly one personal workspace
+CREATE UNIQUE INDEX IF NOT EXISTS workspaces_personal_idx ON workspaces (created_by) WHERE personal;
+
+CREATE TABLE
+    IF NOT EXISTS workspace_members (
+        workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        role workspace_role NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (workspace_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS workspace_members_user_id_idx ON workspace_members (user_id);
+
+CREATE TABLE
+    IF NOT EXISTS workspace_invitations (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+        email VARCHAR(255) NOT NULL,
+        role workspace_role NOT NULL,
+        invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+        expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+        accepted_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- At most one open invitation per address and workspace, inviting again replaces it
+CREATE UNIQUE INDEX IF NOT EXISTS workspace_invitations_email_idx ON workspace_invitations (workspace_id, LOWER(email)) WHERE accepted_at IS NULL;
+
+CREATE OR REPLACE FUNCTION create_personal_workspace() RETURNS TRIGGER AS $$
+DECLARE
+    new_workspace_id UUID;
+BEGIN
+    INSERT INTO workspaces (name, personal, created_by) VALUES ('Personal', TRUE, NEW.id)
+    RETURNING id INTO new_workspace_id;
+    INSERT INTO workspace_members (workspace_id, user_id, role) VALUES (new_workspace_id, NEW.id, 'owner');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_personal_workspace
+AFTER INSERT ON users
+FOR EACH ROW EXECUTE FUNCTION create_personal_workspace();
+
+-- Existing users get their personal workspace, which takes over everything they had
+INSERT INTO workspaces (name, personal, created_by) SELECT 'Personal', TRUE, id FROM users;
+INSERT INTO workspace_members (workspace_id, user_id, role) SELECT id, created_by, 'owner' FROM workspaces WHERE personal;
+
+ALTER TABLE projects ADD COLUMN workspace_id UUID REFERENCES workspaces(id) ON DELETE CASCADE;
+UPDATE projects p SET workspace_id = w.id FROM workspaces w WHERE w.personal AND w.created_by = p.user_id;
+ALTER TABLE projects ALTER COLUMN workspace_id SET NOT NULL;
+CREATE INDEX IF NOT EXISTS projects_workspace_id_idx ON projects (workspace_id);
+
+ALTER TABLE lists ADD COLUMN workspace_id UUID REFERENCES workspaces(id) ON DELETE CASCADE;
+-- Items filed under a project shared with their creator move along with that project
+UPDATE lists l SET workspace_id = COALESCE(
+    (SELECT p.workspace_id FROM projects p WHERE p.id = l.project_id),
+    (SELECT w.id FROM workspaces w WHERE w.personal AND w.created_by = l.user_id)
+);
+ALTER TABLE lists ALTER COLUMN workspace_id SET NOT NULL;
+CREATE INDEX IF NOT EXISTS lists_workspace_id_idx ON lists (workspace_id, user_id);
+
+-- An item can only be filed under a project of its own workspace
+CREATE OR REPLACE FUNCTION check_list_project_workspace() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.project_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM projects WHERE id = NEW.project_id AND workspace_id = NEW.workspace_id
+    ) THEN
+        RAISE EXCEPTION 'project % belongs to another workspace', NEW.project_id
+            USING ERRCODE = 'check_violation', CONSTRAINT = 'lists_project_workspace';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_project_workspace
+BEFORE INSERT OR UPDATE OF project_id, workspace_id ON lists
+FOR EACH ROW EXECUTE FUNCTION check_list_project_workspace();
+
+-- What being in a workspace allows on everything in it: admins and owners manage it all,
+-- members edit, guests only see what was shared with them
+CREATE OR REPLACE FUNCTION workspace_share_role(p_workspace_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT CASE m.role
+        WHEN 'owner' THEN 'owner'::share_role
+        WHEN 'admin' THEN 'owner'::share_role
+        WHEN 'member' THEN 'editor'::share_role
+    END
+    FROM workspace_members m
+    WHERE m.workspace_id = p_workspace_id AND m.user_id = p_user_id
+$$ LANGUAGE sql STABLE;
+
+-- Creators only keep owning what they made while they are still in its workspace
+CREATE OR REPLACE FUNCTION project_role(p_project_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT GREATEST(
+        CASE WHEN p.user_id = p_user_id AND workspace_share_role(p.workspace_id, p_user_id) IS NOT NULL
+            THEN 'owner'::share_role END,
+        (SELECT s.role FROM project_shares s WHERE s.project_id = p.id AND s.user_id = p_user_id),
+        workspace_share_role(p.workspace_id, p_user_id)
+    )
+    FROM projects p
+    WHERE p.id = p_project_id
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION list_role(p_list_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT GREATEST(
+        CASE WHEN l.user_id = p_user_id AND workspace_share_role(l.workspace_id, p_user_id) IS NOT NULL
+            THEN 'owner'::share_role END,
+        (SELECT s.role FROM list_shares s WHERE s.list_id = l.id AND s.user_id = p_user_id),
+        project_role(l.project_id, p_user_id),
+        workspace_share_role(l.workspace_id, p_user_id)
+    )
+    FROM lists l
+    WHERE l.id = p_list_id
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,20 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS lists_prune_assignees ON lists;
+DROP TRIGGER IF EXISTS workspace_members_prune_assignees ON workspace_members;
+DROP TRIGGER IF EXISTS project_shares_prune_assignees ON project_shares;
+DROP TRIGGER IF EXISTS list_shares_prune_assignees ON list_shares;
+DROP FUNCTION IF EXISTS prune_list_assignees();
+
+CREATE OR REPLACE FUNCTION list_role(p_list_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT GREATEST(
+        CASE WHEN l.user_id = p_user_id AND workspace_share_role(l.workspace_id, p_user_id) IS NOT NULL
+            THEN 'owner'::share_role END,
+        (SELECT s.role FROM list_shares s WHERE s.list_id = l.id AND s.user_id = p_user_id),
+        project_role(l.project_id, p_user_id),
+        workspace_share_role(l.workspace_id, p_user_id)
+    )
+    FROM lists l
+    WHERE l.id = p_list_id
+$$ LANGUAGE sql STABLE;
+
+DROP TABLE IF EXISTS list_assignees;
@@ -0,0 +1,66 @@
+-- Add up migration script here
+CREATE TABLE
+    IF NOT EXISTS list_assignees (
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        assigned_by UUID REFERENCES users(id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (list_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS list_assignees_user_id_idx ON list_assignees (user_id);
+
+-- Being assigned to an item lets workspace members (guests included) edit it, for as long as
+-- they stay in the item's workspace
+CREATE OR REPLACE FUNCTION list_role(p_list_id UUID, p_user_id UUID) RETURNS share_role AS $$
+    SELECT GREATEST(
+        CASE WHEN l.user_id = p_user_id AND workspace_share_role(l.workspace_id, p_user_id) IS NOT NULL
+            THEN 'owner'::share_role END,
+        (SELECT s.role FROM list_shares s WHERE s.list_id = l.id AND s.user_id = p_user_id),
+        project_role(l.project_id, p_user_id),
+        workspace_share_role(l.workspace_id, p_user_id),
+        CASE WHEN EXISTS (SELECT 1 FROM list_assignees a WHERE a.list_id = l.id AND a.user_id = p_user_id)
+            AND EXISTS (SELECT 1 FROM workspace_members m WHERE m.workspace_id = l.workspace_id AND m.user_id = p_user_id)
+            THEN 'editor'::share_role END
+    )
+    FROM lists l
+    WHERE l.id = p_list_id
+$$ LANGUAGE sql STABLE;
+
+-- Assignees who can no longer see an item are taken off it, whichever way they lost access
+CREATE OR REPLACE FUNCTION prune_list_assignees() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'list_shares' THEN
+        DELETE FROM list_assignees a
+        WHERE a.list_id = OLD.list_id AND a.user_id = OLD.user_id AND list_role(a.list_id, a.user_id) IS NULL;
+    ELSIF TG_TABLE_NAME = 'project_shares' THEN
+        DELETE FROM list_assignees a USING lists l
+        WHERE l.id = a.list_id AND l.project_id = OLD.project_id AND a.user_id = OLD.user_id
+            AND list_role(a.list_id, a.user_id) IS NULL;
+    ELSIF TG_TABLE_NAME = 'workspace_members' THEN
+        DELETE FROM list_assignees a USING lists l
+        WHERE l.id = a.list_id AND l.workspace_id = OLD.workspace_id AND a.user_id = OLD.user_id
+            AND list_role(a.list_id, a.user_id) IS NULL;
+    ELSIF TG_TABLE_NAME = 'lists' THEN
+        DELETE FROM list_assignees a
+        WHERE a.list_id = OLD.id AND list_role(a.list_id, a.user_id) IS NULL;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER list_shares_prune_assignees
+AFTER DELETE ON list_shares
+FOR EACH ROW EXECUTE FUNCTION prune_list_assignees();
+
+CREATE TRIGGER project_shares_prune_assignees
+AFTER DELETE ON project_shares
+FOR EACH ROW EXECUTE FUNCTION prune_list_assignees();
+
+CREATE TRIGGER workspace_members_prune_assignees
+AFTER DELETE ON workspace_members
+FOR EACH ROW EXECUTE FUNCTION prune_list_assignees();
+
+CREATE TRIGGER lists_prune_assignees
+AFTER UPDATE OF project_id, workspace_id ON lists
+FOR EACH ROW EXECUTE FUNCTION prune_list_assignees();
@@ -0,0 +1,25 @@
+-- Add up migration script here
+CREATE TABLE
+    IF NOT EXISTS list_comments (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+        -- Replies always hang off the thread's first comment, so threads are one level deep
+        parent_id UUID REFERENCES list_comments(id) ON DELETE CASCADE,
+        body TEXT NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        edited_at TIMESTAMP WITH TIME ZONE,
+        -- Set instead of deleting comments that still have replies
+        deleted_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS list_comments_list_id_idx ON list_comments (list_id, created_at) WHERE parent_id IS NULL;
+CREATE INDEX IF NOT EXISTS list_comments_parent_id_idx ON list_comments (parent_id, created_at);
+
+-- Who a comment mentions, so editing it only notifies people who weren't mentioned before
+CREATE TABLE
+    IF NOT EXISTS comment_mentions (
+        comment_id UUID NOT NULL REFERENCES list_comments(id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        PRIMARY KEY (comment_id, user_id)
+);
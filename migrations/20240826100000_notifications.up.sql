@@ -0,0 +1,31 @@
+-- Add up migration script here
+CREATE TYPE notification_type AS ENUM ('assigned', 'mentioned', 'due_soon', 'shared');
+
+CREATE TABLE
+    IF NOT EXISTS notifications (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        type notification_type NOT NULL,
+        actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+        list_id UUID REFERENCES lists(id) ON DELETE CASCADE,
+        project_id UUID REFERENCES projects(id) ON DELETE CASCADE,
+        comment_id UUID REFERENCES list_comments(id) ON DELETE CASCADE,
+        message TEXT NOT NULL,
+        -- Events that must only ever be reported once, such as an item coming due
+        dedupe_key TEXT,
+        read_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_id_idx ON notifications (user_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS notifications_unread_idx ON notifications (user_id) WHERE read_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS notifications_dedupe_key_idx ON notifications (user_id, dedupe_key) WHERE dedupe_key IS NOT NULL;
+
+-- Which events also go out by email; events without a row do
+CREATE TABLE
+    IF NOT EXISTS notification_preferences (
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        type notification_type NOT NULL,
+        email BOOLEAN NOT NULL,
+        PRIMARY KEY (user_id, type)
+);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS lists_reschedule_reminders ON lists;
+DROP FUNCTION IF EXISTS reschedule_list_reminders();
+DROP TRIGGER IF EXISTS list_reminders_fire_at ON list_reminders;
+DROP FUNCTION IF EXISTS set_reminder_fire_at();
+DROP TABLE IF EXISTS list_reminders;
+DROP TYPE IF EXISTS reminder_status;
@@ -0,0 +1,56 @@
+-- Add up migration script here
+CREATE TYPE reminder_status AS ENUM ('pending', 'sent', 'failed', 'skipped');
+
+CREATE TABLE
+    IF NOT EXISTS list_reminders (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        list_id UUID NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        -- Either a fixed time or a number of minutes before the item is due
+        remind_at TIMESTAMP WITH TIME ZONE,
+        offset_minutes INTEGER CHECK (offset_minutes >= 0),
+        -- When the reminder goes out, kept in sync with the item's due date; NULL while a relative
+        -- reminder's item has no due date
+        fire_at TIMESTAMP WITH TIME ZONE,
+        status reminder_status NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        -- Set after a failed attempt, to when the next one is due
+        retry_at TIMESTAMP WITH TIME ZONE,
+        last_error TEXT,
+        sent_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        CONSTRAINT list_reminders_time CHECK ((remind_at IS NULL) <> (offset_minutes IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS list_reminders_list_id_idx ON list_reminders (list_id, user_id);
+CREATE INDEX IF NOT EXISTS list_reminders_pending_idx ON list_reminders (COALESCE(retry_at, fire_at)) WHERE status = 'pending';
+
+CREATE OR REPLACE FUNCTION set_reminder_fire_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.fire_at := COALESCE(
+        NEW.remind_at,
+        (SELECT l.due_at - make_interval(mins => NEW.offset_minutes) FROM lists l WHERE l.id = NEW.list_id)
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER list_reminders_fire_at
+BEFORE INSERT ON list_reminders
+FOR EACH ROW EXECUTE FUNCTION set_reminder_fire_at();
+
+-- Moving the due date reschedules relative reminders, and they go out again even if they already did
+CREATE OR REPLACE FUNCTION reschedule_list_reminders() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE list_reminders
+    SET fire_at = NEW.due_at - make_interval(mins => offset_minutes),
+        status = 'pending', attempts = 0, retry_at = NULL, last_error = NULL, sent_at = NULL
+    WHERE list_id = NEW.id AND offset_minutes IS NOT NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_reschedule_reminders
+AFTER UPDATE OF due_at ON lists
+FOR EACH ROW WHEN (OLD.due_at IS DISTINCT FROM NEW.due_at)
+EXECUTE FUNCTION reschedule_list_reminders();
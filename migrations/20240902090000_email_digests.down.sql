@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS email_digests_schedule ON email_digests;
+DROP FUNCTION IF EXISTS schedule_email_digest();
+DROP FUNCTION IF EXISTS next_digest_at(digest_frequency, TIME, SMALLINT, TEXT, TIMESTAMP WITH TIME ZONE);
+DROP TABLE IF EXISTS email_digests;
+DROP TYPE IF EXISTS digest_frequency;
@@ -0,0 +1,54 @@
+-- Add up migration script here
+CREATE TYPE digest_frequency AS ENUM ('daily', 'weekly');
+
+-- Having a row means the user opted in to the digest
+CREATE TABLE
+    IF NOT EXISTS email_digests (
+        user_id UUID PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        frequency digest_frequency NOT NULL,
+        -- Local time of day in `timezone`
+        send_time TIME NOT NULL,
+        -- ISO day of the week for weekly digests, 1 is Monday
+        weekday SMALLINT CHECK (weekday BETWEEN 1 AND 7),
+        timezone TEXT NOT NULL,
+        next_send_at TIMESTAMP WITH TIME ZONE NOT NULL,
+        last_sent_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        CONSTRAINT email_digests_weekday CHECK ((frequency = 'weekly') = (weekday IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS email_digests_next_send_at_idx ON email_digests (next_send_at);
+
+-- The first time after `after` that the digest is due, worked out in the user's timezone so it
+-- keeps going out at the same local time across daylight saving changes
+CREATE OR REPLACE FUNCTION next_digest_at(
+    frequency digest_frequency, send_time TIME, weekday SMALLINT, timezone TEXT, after TIMESTAMP WITH TIME ZONE
+) RETURNS TIMESTAMP WITH TIME ZONE AS $$
+DECLARE
+    step INTERVAL := CASE WHEN frequency = 'weekly' THEN INTERVAL '7 days' ELSE INTERVAL '1 day' END;
+    candidate TIMESTAMP := date_trunc('day', after AT TIME ZONE timezone) + send_time;
+BEGIN
+    IF frequency = 'weekly' THEN
+        candidate := candidate + make_interval(days => (weekday - EXTRACT(ISODOW FROM candidate)::INT + 7) % 7);
+    END IF;
+    -- Also steps over a local time that daylight saving maps back before `after`
+    WHILE (candidate AT TIME ZONE timezone) <= after LOOP
+        candidate := candidate + step;
+    END LOOP;
+    RETURN candidate AT TIME ZONE timezone;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+CREATE OR REPLACE FUNCTION schedule_email_digest() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.next_send_at := next_digest_at(NEW.frequency, NEW.send_time, NEW.weekday, NEW.timezone, NOW());
+    NEW.updated_at := NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Changing the schedule takes effect from now on
+CREATE TRIGGER email_digests_schedule
+BEFORE INSERT OR UPDATE OF frequency, send_time, weekday, timezone ON email_digests
+FOR EACH ROW EXECUTE FUNCTION schedule_email_digest();
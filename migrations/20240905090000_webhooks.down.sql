@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS list_comments_webhook_events ON list_comments;
+DROP FUNCTION IF EXISTS comment_webhook_events();
+DROP TRIGGER IF EXISTS lists_webhook_events ON lists;
+DROP FUNCTION IF EXISTS list_webhook_events();
+DROP FUNCTION IF EXISTS enqueue_webhook_event(TEXT, UUID, JSONB, UUID);
+DROP FUNCTION IF EXISTS webhook_list_json(lists);
+DROP TABLE IF EXISTS webhook_delivery_attempts;
+DROP TABLE IF EXISTS webhook_deliveries;
+DROP TYPE IF EXISTS webhook_delivery_status;
+DROP TABLE IF EXISTS webhooks;
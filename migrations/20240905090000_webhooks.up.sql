@@ -0,0 +1,122 @@
+-- Add up migration script here
+CREATE TABLE
+    IF NOT EXISTS webhooks (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+        url TEXT NOT NULL,
+        -- Signs every payload; only shown when the webhook is created
+        secret TEXT NOT NULL,
+        events TEXT[] NOT NULL,
+        active BOOLEAN NOT NULL DEFAULT TRUE,
+        -- Deliveries that ran out of attempts in a row; the webhook is disabled past a limit
+        consecutive_failures INTEGER NOT NULL DEFAULT 0,
+        disabled_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhooks_user_id_idx ON webhooks (user_id);
+
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'delivered', 'failed');
+
+-- The outbox: events are queued in the same transaction as the change that caused them
+CREATE TABLE
+    IF NOT EXISTS webhook_deliveries (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+        event TEXT NOT NULL,
+        payload JSONB NOT NULL,
+        status webhook_delivery_status NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        last_status_code INTEGER,
+        last_error TEXT,
+        delivered_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_id_idx ON webhook_deliveries (webhook_id, created_at);
+CREATE INDEX IF NOT EXISTS webhook_deliveries_pending_idx ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+
+CREATE TABLE
+    IF NOT EXISTS webhook_delivery_attempts (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        delivery_id UUID NOT NULL REFERENCES webhook_deliveries(id) ON DELETE CASCADE,
+        status_code INTEGER,
+        error TEXT,
+        duration_ms INTEGER NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhook_delivery_attempts_delivery_id_idx ON webhook_delivery_attempts (delivery_id, created_at);
+
+-- An item as webhooks see it, with the same field names as the API
+CREATE OR REPLACE FUNCTION webhook_list_json(l lists) RETURNS JSONB AS $$
+    SELECT jsonb_build_object(
+        'id', l.id, 'title', l.title, 'user_id', l.user_id, 'descr', l.descr, 'body', l.body,
+        'importance', l.importance, 'position', l.position, 'version', l.version, 'status', l.status,
+        'project_id', l.project_id, 'workspace_id', l.workspace_id, 'dueAt', l.due_at,
+        'completedAt', l.completed_at, 'createdAt', l.created_at, 'updatedAt', l.updated_at,
+        'archivedAt', l.archived_at, 'deletedAt', l.deleted_at
+    )
+$$ LANGUAGE sql STABLE;
+
+-- Queues the event for every active webhook subscribed to it whose owner can see the item. The
+-- actor is whoever the application recorded for the transaction, else `p_actor_id`.
+CREATE OR REPLACE FUNCTION enqueue_webhook_event(p_event TEXT, p_list_id UUID, p_data JSONB, p_actor_id UUID) RETURNS VOID AS $$
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT w.id, p_event, jsonb_build_object(
+        'event', p_event,
+        'createdAt', NOW(),
+        'actorId', COALESCE(NULLIF(current_setting('app.actor_id', true), '')::uuid, p_actor_id),
+        'data', p_data
+    )
+    FROM webhooks w
+    WHERE w.active AND p_event = ANY(w.events) AND list_role(p_list_id, w.user_id) IS NOT NULL
+$$ LANGUAGE sql;
+
+-- One event per change, the most specific one that applies
+CREATE OR REPLACE FUNCTION list_webhook_events() RETURNS TRIGGER AS $$
+DECLARE
+    event TEXT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        event := 'list.created';
+    ELSIF OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        event := 'list.deleted';
+    ELSIF OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+        event := 'list.restored';
+    ELSIF OLD.archived_at IS NULL AND NEW.archived_at IS NOT NULL THEN
+        event := 'list.archived';
+    ELSIF OLD.archived_at IS NOT NULL AND NEW.archived_at IS NULL THEN
+        event := 'list.unarchived';
+    ELSIF OLD.status <> 'done' AND NEW.status = 'done' THEN
+        event := 'list.completed';
+    ELSIF (webhook_list_json(OLD) - 'updatedAt' - 'version') <> (webhook_list_json(NEW) - 'updatedAt' - 'version') THEN
+        event := 'list.updated';
+    ELSE
+        RETURN NULL;
+    END IF;
+
+    PERFORM enqueue_webhook_event(event, NEW.id, jsonb_build_object('list', webhook_list_json(NEW)), NULL);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_webhook_events
+AFTER INSERT OR UPDATE ON lists
+FOR EACH ROW EXECUTE FUNCTION list_webhook_events();
+
+CREATE OR REPLACE FUNCTION comment_webhook_events() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM enqueue_webhook_event('comment.created', NEW.list_id, jsonb_build_object('comment', jsonb_build_object(
+        'id', NEW.id, 'list_id', NEW.list_id, 'user_id', NEW.user_id, 'parent_id', NEW.parent_id,
+        'body', NEW.body, 'createdAt', NEW.created_at
+    )), NEW.user_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER list_comments_webhook_events
+AFTER INSERT ON list_comments
+FOR EACH ROW EXECUTE FUNCTION comment_webhook_events();
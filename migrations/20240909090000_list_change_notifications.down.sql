@@ -0,0 +1,34 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS list_comments_notify_change ON list_comments;
+DROP FUNCTION IF EXISTS notify_comment_change();
+DROP TRIGGER IF EXISTS lists_notify_change ON lists;
+DROP FUNCTION IF EXISTS notify_list_change();
+
+CREATE OR REPLACE FUNCTION list_webhook_events() RETURNS TRIGGER AS $$
+DECLARE
+    event TEXT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        event := 'list.created';
+    ELSIF OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        event := 'list.deleted';
+    ELSIF OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+        event := 'list.restored';
+    ELSIF OLD.archived_at IS NULL AND NEW.archived_at IS NOT NULL THEN
+        event := 'list.archived';
+    ELSIF OLD.archived_at IS NOT NULL AND NEW.archived_at IS NULL THEN
+        event := 'list.unarchived';
+    ELSIF OLD.status <> 'done' AND NEW.status = 'done' THEN
+        event := 'list.completed';
+    ELSIF (webhook_list_json(OLD) - 'updatedAt' - 'version') <> (webhook_list_json(NEW) - 'updatedAt' - 'version') THEN
+        event := 'list.updated';
+    ELSE
+        RETURN NULL;
+    END IF;
+
+    PERFORM enqueue_webhook_event(event, NEW.id, jsonb_build_object('list', webhook_list_json(NEW)), NULL);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS list_change_event(lists, lists);
@@ -0,0 +1,75 @@
+-- Add up migration script here
+
+-- What an update to an item amounts to, the most specific event that applies, or NULL when
+-- nothing clients care about changed
+CREATE OR REPLACE FUNCTION list_change_event(old_list lists, new_list lists) RETURNS TEXT AS $$
+BEGIN
+    IF old_list.deleted_at IS NULL AND new_list.deleted_at IS NOT NULL THEN
+        RETURN 'list.deleted';
+    ELSIF old_list.deleted_at IS NOT NULL AND new_list.deleted_at IS NULL THEN
+        RETURN 'list.restored';
+    ELSIF old_list.archived_at IS NULL AND new_list.archived_at IS NOT NULL THEN
+        RETURN 'list.archived';
+    ELSIF old_list.archived_at IS NOT NULL AND new_list.archived_at IS NULL THEN
+        RETURN 'list.unarchived';
+    ELSIF old_list.status <> 'done' AND new_list.status = 'done' THEN
+        RETURN 'list.completed';
+    ELSIF (webhook_list_json(old_list) - 'updatedAt' - 'version') <> (webhook_list_json(new_list) - 'updatedAt' - 'version') THEN
+        RETURN 'list.updated';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+CREATE OR REPLACE FUNCTION list_webhook_events() RETURNS TRIGGER AS $$
+DECLARE
+    event TEXT := CASE WHEN TG_OP = 'INSERT' THEN 'list.created' ELSE list_change_event(OLD, NEW) END;
+BEGIN
+    IF event IS NOT NULL THEN
+        PERFORM enqueue_webhook_event(event, NEW.id, jsonb_build_object('list', webhook_list_json(NEW)), NULL);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Servers LISTEN on list_changes and pass these on to connected clients. Payloads only carry ids,
+-- well under the 8000 byte NOTIFY limit; clients fetch what they need.
+CREATE OR REPLACE FUNCTION notify_list_change() RETURNS TRIGGER AS $$
+DECLARE
+    event TEXT := CASE WHEN TG_OP = 'INSERT' THEN 'list.created' ELSE list_change_event(OLD, NEW) END;
+BEGIN
+    IF event IS NOT NULL THEN
+        PERFORM pg_notify('list_changes', json_build_object(
+            'event', event,
+            'list_id', NEW.id,
+            'workspace_id', NEW.workspace_id,
+            'version', NEW.version,
+            'actorId', NULLIF(current_setting('app.actor_id', true), '')::uuid,
+            'createdAt', NOW()
+        )::text);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_notify_change
+AFTER INSERT OR UPDATE ON lists
+FOR EACH ROW EXECUTE FUNCTION notify_list_change();
+
+CREATE OR REPLACE FUNCTION notify_comment_change() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('list_changes', json_build_object(
+        'event', 'comment.created',
+        'list_id', NEW.list_id,
+        'workspace_id', (SELECT l.workspace_id FROM lists l WHERE l.id = NEW.list_id),
+        'comment_id', NEW.id,
+        'actorId', NEW.user_id,
+        'createdAt', NOW()
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER list_comments_notify_change
+AFTER INSERT ON list_comments
+FOR EACH ROW EXECUTE FUNCTION notify_comment_change();
@@ -0,0 +1,22 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS lists_access_changes ON lists;
+DROP TRIGGER IF EXISTS workspace_members_access_changes ON workspace_members;
+DROP TRIGGER IF EXISTS projects_access_changes ON projects;
+DROP TRIGGER IF EXISTS project_shares_access_changes ON project_shares;
+DROP TRIGGER IF EXISTS list_shares_access_changes ON list_shares;
+DROP FUNCTION IF EXISTS record_list_access_changes();
+DROP TRIGGER IF EXISTS workspaces_record_tombstones ON workspaces;
+DROP TRIGGER IF EXISTS lists_record_tombstones ON lists;
+DROP FUNCTION IF EXISTS record_list_tombstones();
+DROP TABLE IF EXISTS list_access_changes;
+
+DROP TRIGGER IF EXISTS list_assignees_touch_list ON list_assignees;
+DROP TRIGGER IF EXISTS list_tags_touch_list ON list_tags;
+DROP FUNCTION IF EXISTS touch_list_change();
+DROP TRIGGER IF EXISTS lists_change_seq ON lists;
+DROP FUNCTION IF EXISTS set_list_change_seq();
+
+DROP INDEX IF EXISTS lists_change_xid_idx;
+DROP INDEX IF EXISTS lists_change_seq_idx;
+ALTER TABLE lists DROP COLUMN IF EXISTS created_xid, DROP COLUMN IF EXISTS change_xid, DROP COLUMN IF EXISTS change_seq;
+DROP SEQUENCE IF EXISTS list_change_seq;
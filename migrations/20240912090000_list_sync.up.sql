@@ -0,0 +1,167 @@
+-- Add up migration script here
+
+-- Orders every change sync clients need to hear about. Values are taken when a change is made
+-- but become visible when its transaction commits, so readers also track transaction ids to
+-- catch changes that commit out of order.
+CREATE SEQUENCE IF NOT EXISTS list_change_seq;
+
+ALTER TABLE lists
+    ADD COLUMN change_seq BIGINT NOT NULL DEFAULT nextval('list_change_seq'),
+    ADD COLUMN change_xid xid8 NOT NULL DEFAULT pg_current_xact_id(),
+    ADD COLUMN created_xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX IF NOT EXISTS lists_change_seq_idx ON lists (change_seq);
+CREATE INDEX IF NOT EXISTS lists_change_xid_idx ON lists (change_xid);
+
+CREATE OR REPLACE FUNCTION set_list_change_seq() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.change_seq := nextval('list_change_seq');
+    NEW.change_xid := pg_current_xact_id();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_change_seq
+BEFORE UPDATE ON lists
+FOR EACH ROW EXECUTE FUNCTION set_list_change_seq();
+
+-- Tags and assignees are part of what clients sync, so changing them counts as changing the item
+CREATE OR REPLACE FUNCTION touch_list_change() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE lists SET change_seq = change_seq
+    WHERE id = CASE WHEN TG_OP = 'DELETE' THEN OLD.list_id ELSE NEW.list_id END;
+    IF TG_OP = 'UPDATE' AND NEW.list_id <> OLD.list_id THEN
+        UPDATE lists SET change_seq = change_seq WHERE id = OLD.list_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER list_tags_touch_list
+AFTER INSERT OR UPDATE OR DELETE ON list_tags
+FOR EACH ROW EXECUTE FUNCTION touch_list_change();
+
+CREATE TRIGGER list_assignees_touch_list
+AFTER INSERT OR UPDATE OR DELETE ON list_assignees
+FOR EACH ROW EXECUTE FUNCTION touch_list_change();
+
+-- Items a user may have gained or lost access to without the item itself changing, and items
+-- deleted for good. Sync reports each one by its current state: still visible, or gone. Rows
+-- are only written for users who could see the item before or can see it now.
+CREATE TABLE
+    IF NOT EXISTS list_access_changes (
+        seq BIGINT PRIMARY KEY NOT NULL DEFAULT nextval('list_change_seq'),
+        xid xid8 NOT NULL DEFAULT pg_current_xact_id(),
+        list_id UUID NOT NULL,
+        user_id UUID NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS list_access_changes_user_id_idx ON list_access_changes (user_id, seq);
+CREATE INDEX IF NOT EXISTS list_access_changes_created_at_idx ON list_access_changes (created_at);
+
+-- Runs before the delete, while list_role can still tell who sees the items. Deleting a
+-- workspace cascades to its members and items in no set order, so it is handled up front.
+CREATE OR REPLACE FUNCTION record_list_tombstones() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO list_access_changes (list_id, user_id)
+    SELECT l.id, u.user_id
+    FROM lists l, LATERAL (
+        SELECT user_id FROM workspace_members WHERE workspace_id = l.workspace_id
+        UNION SELECT user_id FROM list_shares WHERE list_id = l.id
+        UNION SELECT user_id FROM project_shares WHERE project_id = l.project_id
+    ) u
+    WHERE CASE WHEN TG_TABLE_NAME = 'workspaces' THEN l.workspace_id = OLD.id ELSE l.id = OLD.id END
+        AND list_role(l.id, u.user_id) IS NOT NULL;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER lists_record_tombstones
+BEFORE DELETE ON lists
+FOR EACH ROW EXECUTE FUNCTION record_list_tombstones();
+
+CREATE TRIGGER workspaces_record_tombstones
+BEFORE DELETE ON workspaces
+FOR EACH ROW EXECUTE FUNCTION record_list_tombstones();
+
+-- Mirrors prune_list_assignees: whichever way access changes, the users concerned re-check the
+-- items involved. Gaining access through an item's own move needs nothing, the move changes it.
+CREATE OR REPLACE FUNCTION record_list_access_changes() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'list_shares' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO list_access_changes (list_id, user_id) VALUES (NEW.list_id, NEW.user_id);
+        ELSIF list_role(OLD.list_id, OLD.user_id) IS NULL THEN
+            INSERT INTO list_access_changes (list_id, user_id) VALUES (OLD.list_id, OLD.user_id);
+        END IF;
+    ELSIF TG_TABLE_NAME = 'project_shares' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO list_access_changes (list_id, user_id)
+            SELECT l.id, NEW.user_id FROM lists l WHERE l.project_id = NEW.project_id;
+        ELSE
+            INSERT INTO list_access_changes (list_id, user_id)
+            SELECT l.id, OLD.user_id FROM lists l
+            WHERE l.project_id = OLD.project_id AND list_role(l.id, OLD.user_id) IS NULL;
+        END IF;
+    ELSIF TG_TABLE_NAME = 'projects' THEN
+        -- Items outlive their project, but its sharees may lose them
+        INSERT INTO list_access_changes (list_id, user_id)
+        SELECT l.id, s.user_id
+        FROM lists l JOIN project_shares s ON s.project_id = l.project_id
+        WHERE l.project_id = OLD.id;
+        RETURN OLD;
+    ELSIF TG_TABLE_NAME = 'workspace_members' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO list_access_changes (list_id, user_id)
+            SELECT l.id, NEW.user_id FROM lists l
+            WHERE l.workspace_id = NEW.workspace_id AND list_role(l.id, NEW.user_id) IS NOT NULL;
+        ELSIF TG_OP = 'UPDATE' AND OLD.role = 'guest' AND NEW.role <> 'guest' THEN
+            INSERT INTO list_access_changes (list_id, user_id)
+            SELECT l.id, NEW.user_id FROM lists l
+            WHERE l.workspace_id = NEW.workspace_id AND list_role(l.id, NEW.user_id) IS NOT NULL;
+        ELSIF TG_OP = 'DELETE' OR (OLD.role <> 'guest' AND NEW.role = 'guest') THEN
+            -- Guests only ever saw what they created, were assigned or had shared with them
+            INSERT INTO list_access_changes (list_id, user_id)
+            SELECT l.id, OLD.user_id FROM lists l
+            WHERE l.workspace_id = OLD.workspace_id AND list_role(l.id, OLD.user_id) IS NULL
+                AND (OLD.role <> 'guest' OR l.user_id = OLD.user_id
+                    OR EXISTS (SELECT 1 FROM list_assignees a WHERE a.list_id = l.id AND a.user_id = OLD.user_id));
+        END IF;
+    ELSIF TG_TABLE_NAME = 'lists' THEN
+        -- Everyone who saw the item where it was, and no longer does
+        INSERT INTO list_access_changes (list_id, user_id)
+        SELECT NEW.id, u.user_id
+        FROM (
+            SELECT user_id FROM workspace_members WHERE workspace_id = OLD.workspace_id AND role <> 'guest'
+            UNION SELECT user_id FROM project_shares WHERE project_id = OLD.project_id
+            UNION SELECT user_id FROM list_assignees WHERE list_id = OLD.id
+            UNION SELECT OLD.user_id
+        ) u
+        WHERE u.user_id IS NOT NULL AND list_role(NEW.id, u.user_id) IS NULL;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Named to run before the *_prune_assignees triggers, while assignments still show who had access
+CREATE TRIGGER list_shares_access_changes
+AFTER INSERT OR DELETE ON list_shares
+FOR EACH ROW EXECUTE FUNCTION record_list_access_changes();
+
+CREATE TRIGGER project_shares_access_changes
+AFTER INSERT OR DELETE ON project_shares
+FOR EACH ROW EXECUTE FUNCTION record_list_access_changes();
+
+CREATE TRIGGER projects_access_changes
+BEFORE DELETE ON projects
+FOR EACH ROW EXECUTE FUNCTION record_list_access_changes();
+
+CREATE TRIGGER workspace_members_access_changes
+AFTER INSERT OR DELETE OR UPDATE OF role ON workspace_members
+FOR EACH ROW EXECUTE FUNCTION record_list_access_changes();
+
+CREATE TRIGGER lists_access_changes
+AFTER UPDATE OF workspace_id, project_id ON lists
+FOR EACH ROW WHEN (OLD.workspace_id IS DISTINCT FROM NEW.workspace_id OR OLD.project_id IS DISTINCT FROM NEW.project_id)
+EXECUTE FUNCTION record_list_access_changes();
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS lists_body_trgm_idx;
+DROP INDEX IF EXISTS lists_descr_trgm_idx;
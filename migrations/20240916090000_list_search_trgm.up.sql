@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Fuzzy search matches descriptions and bodies too, not only titles
+CREATE INDEX IF NOT EXISTS lists_descr_trgm_idx ON lists USING GIN (descr gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS lists_body_trgm_idx ON lists USING GIN (body gin_trgm_ops);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- When the request holding a key started, cleared once its response is stored. Keys held for too
+-- long belong to requests that died and can be taken over by a retry.
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS locked_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,12 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION reschedule_list_reminders() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE list_reminders
+    SET fire_at = NEW.due_at - make_interval(mins => offset_minutes),
+        status = 'pending', attempts = 0, retry_at = NULL, last_error = NULL, sent_at = NULL
+    WHERE list_id = NEW.id AND offset_minutes IS NOT NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE list_reminders DROP COLUMN IF EXISTS locked_until;
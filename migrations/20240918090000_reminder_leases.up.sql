@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- Reminders are claimed for a while and sent outside the claiming transaction; one whose worker
+-- died comes up again once its claim runs out.
+ALTER TABLE list_reminders ADD COLUMN IF NOT EXISTS locked_until TIMESTAMP WITH TIME ZONE;
+
+-- Rescheduling also drops the claim, so a send already under way isn't recorded against the new time
+CREATE OR REPLACE FUNCTION reschedule_list_reminders() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE list_reminders
+    SET fire_at = NEW.due_at - make_interval(mins => offset_minutes),
+        status = 'pending', attempts = 0, retry_at = NULL, last_error = NULL, sent_at = NULL, locked_until = NULL
+    WHERE list_id = NEW.id AND offset_minutes IS NOT NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
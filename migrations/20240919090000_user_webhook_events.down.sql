@@ -0,0 +1,6 @@
+-- Add down migration script here
+DELETE FROM webhook_deliveries WHERE event LIKE 'user.%';
+UPDATE webhooks SET events = ARRAY(SELECT e FROM unnest(events) AS e WHERE e NOT LIKE 'user.%');
+DROP TRIGGER IF EXISTS users_webhook_events ON users;
+DROP FUNCTION IF EXISTS user_webhook_events();
+DROP FUNCTION IF EXISTS webhook_user_json(users);
@@ -0,0 +1,43 @@
+-- Add up migration script here
+CREATE OR REPLACE FUNCTION webhook_user_json(u users) RETURNS JSONB AS $$
+    SELECT jsonb_build_object(
+        'id', u.id, 'username', u.username, 'email', u.email, 'img', u.img,
+        'email_verified', u.email_verified, 'createdAt', u.created_at, 'updatedAt', u.updated_at
+    )
+$$ LANGUAGE sql STABLE;
+
+-- Account events only go to the user's own webhooks
+CREATE OR REPLACE FUNCTION user_webhook_events() RETURNS TRIGGER AS $$
+DECLARE
+    events TEXT[] := '{}';
+BEGIN
+    IF OLD.password IS DISTINCT FROM NEW.password THEN
+        events := array_append(events, 'user.password_changed');
+    END IF;
+    IF OLD.img IS DISTINCT FROM NEW.img THEN
+        events := array_append(events, 'user.avatar_updated');
+    END IF;
+    IF NOT COALESCE(OLD.email_verified, false) AND COALESCE(NEW.email_verified, false) THEN
+        events := array_append(events, 'user.email_verified');
+    END IF;
+    IF OLD.username IS DISTINCT FROM NEW.username OR OLD.email IS DISTINCT FROM NEW.email THEN
+        events := array_append(events, 'user.updated');
+    END IF;
+
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT w.id, e.event, jsonb_build_object(
+        'event', e.event,
+        'createdAt', NOW(),
+        'actorId', COALESCE(NULLIF(current_setting('app.actor_id', true), '')::uuid, NEW.id),
+        'data', jsonb_build_object('user', webhook_user_json(NEW))
+    )
+    FROM unnest(events) AS e(event)
+    JOIN webhooks w ON w.user_id = NEW.id AND w.active AND e.event = ANY(w.events);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_webhook_events
+AFTER UPDATE ON users
+FOR EACH ROW EXECUTE FUNCTION user_webhook_events();
@@ -0,0 +1,85 @@
+use std::str::FromStr;
+
+const DEFAULT_ATTACHMENT_TYPES: &str = "image/png,image/jpeg,image/gif,image/webp,application/pdf,text/plain,text/markdown,text/csv,application/zip";
+
+/// Settings that can be tuned through environment variables, all with sensible defaults.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// How long deleted list items stay in the trash before they are purged for good.
+    pub trash_retention_days: i64,
+    /// Completed items are archived after this many days; 0 turns auto-archiving off.
+    pub auto_archive_after_days: i64,
+    /// Maximum lengths, in characters, of a list item's title, description and Markdown body.
+    pub max_title_length: usize,
+    pub max_descr_length: usize,
+    pub max_body_length: usize,
+    /// Maximum length, in characters, of a comment.
+    pub max_comment_length: usize,
+    /// Largest attachment accepted, in bytes.
+    pub max_attachment_size: usize,
+    /// MIME types attachments may have, as detected from their content where possible.
+    pub allowed_attachment_types: Vec<String>,
+    /// Assignees (or the creator, for unassigned items) are notified this many hours before an
+    /// item is due; 0 turns these notifications off.
+    pub due_soon_hours: i64,
+    /// Frontend page that accepts workspace invitations; the signed token is added as `?token=`.
+    pub invitation_accept_url: String,
+    /// How long an invitation link stays valid.
+    pub invitation_ttl_days: i64,
+    /// Lets webhooks call loopback and private network addresses, e.g. a receiver on localhost
+    /// during development. Off by default so webhooks can't reach internal services.
+    pub webhook_allow_private_urls: bool,
+    /// Finished webhook deliveries and their attempts are kept this many days.
+    pub webhook_log_retention_days: i64,
+    /// How long a sync token stays usable. Deletions are remembered this long, older tokens
+    /// have to sync again from scratch.
+    pub sync_token_ttl_days: i64,
+}
+
+impl Config {
+    pub fn init() -> Config {
+        Config {
+            trash_retention_days: env_or("TRASH_RETENTION_DAYS", 30),
+            auto_archive_after_days: env_or("AUTO_ARCHIVE_AFTER_DAYS", 0),
+            max_title_length: env_or("MAX_TITLE_LENGTH", 200),
+            max_descr_length: env_or("MAX_DESCR_LENGTH", 1000),
+            max_body_length: env_or("MAX_BODY_LENGTH", 100_000),
+            max_comment_length: env_or("MAX_COMMENT_LENGTH", 10_000),
+            max_attachment_size: env_or("MAX_ATTACHMENT_SIZE", 10 * 1024 * 1024),
+            allowed_attachment_types: env_or(
+                "ALLOWED_ATTACHMENT_TYPES",
+                DEFAULT_ATTACHMENT_TYPES.to_string(),
+            )
+            .split(',')
+            .map(|mime| mime.trim().to_lowercase())
+            .filter(|mime| !mime.is_empty())
+            .collect(),
+            due_soon_hours: env_or("DUE_SOON_HOURS", 24),
+            invitation_accept_url: env_or(
+                "INVITATION_ACCEPT_URL",
+                "http://localhost:3000/invitations/accept".to_string(),
+            ),
+            invitation_ttl_days: env_or("INVITATION_TTL_DAYS", 7),
+            webhook_allow_private_urls: env_or("WEBHOOK_ALLOW_PRIVATE_URLS", false),
+            webhook_log_retention_days: env_or("WEBHOOK_LOG_RETENTION_DAYS", 30),
+            sync_token_ttl_days: env_or("SYNC_TOKEN_TTL_DAYS", 90),
+        }
+    }
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    match std::env::var(key) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            // `alloc::string::String` reads better as `String`
+            let expected = std::any::type_name::<T>()
+                .rsplit("::")
+                .next()
+                .unwrap_or_default();
+            panic!(
+                "{} has an invalid value {:?}, expected a {}",
+                key, value, expected
+            )
+        }),
+        Err(_) => default,
+    }
+}
@@ -0,0 +1,243 @@
+use crate::{
+    handlers::{notify, require_list_role, NewNotification},
+    models::{ListAssigneeModel, ListModel, NotificationType, ShareRole, UserModel},
+    schemas::AssignListSchema,
+    utils::send_assignment_mail,
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+/// Most people a single request can assign at once.
+const MAX_ASSIGNEES_PER_REQUEST: usize = 50;
+
+pub async fn get_assignees_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+    get_assignees(&id, &data.db).await
+}
+
+/// Editors assign people who can already see the item. Assigning a workspace member who can't
+/// see it yet gives them edit access to it, so that takes owner access, like sharing does.
+/// Newly assigned people are notified.
+pub async fn assign_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<AssignListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let mut names: Vec<String> = body
+        .users
+        .iter()
+        .map(|user| user.trim().to_string())
+        .filter(|user| !user.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.is_empty() || names.len() > MAX_ASSIGNEES_PER_REQUEST {
+        let error_response = json!({"status": "fail", "message": format!("Provide between 1 and {} users to assign", MAX_ASSIGNEES_PER_REQUEST)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let role = require_list_role(&id, &current_user.id, ShareRole::Editor, &data.db).await?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot assign this item: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let lowercase_names: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+    let candidates = sqlx::query!(
+        r#"SELECT u.id, u.username, u.email,
+            list_role($1, u.id) AS "role: ShareRole",
+            EXISTS (SELECT 1 FROM workspace_members m JOIN lists l ON l.workspace_id = m.workspace_id
+                WHERE l.id = $1 AND m.user_id = u.id) AS "in_workspace!"
+        FROM users u WHERE u.username = ANY($2) OR LOWER(u.email) = ANY($3)"#,
+        id,
+        &names,
+        &lowercase_names
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let missing: Vec<&str> = names
+        .iter()
+        .filter(|name| {
+            !candidates.iter().any(|user| {
+                &user.username == *name || user.email.to_lowercase() == name.to_lowercase()
+            })
+        })
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        let error_response = json!({"status": "fail", "message": format!("User(s) not found: {}", missing.join(", "))});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    for user in &candidates {
+        if user.role.is_some() {
+            continue;
+        }
+        if !user.in_workspace {
+            let error_response = json!({"status": "fail", "message": format!("{} cannot see this item, share it with them first", user.username)});
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+        if role < ShareRole::Owner {
+            let error_response = json!({"status": "fail", "message": format!("You need owner access to this item to assign it to {}, who cannot see it yet", user.username)});
+            return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        }
+    }
+
+    let user_ids: Vec<Uuid> = candidates.iter().map(|user| user.id).collect();
+    let assigned = sqlx::query_scalar!(
+        "INSERT INTO list_assignees (list_id, user_id, assigned_by) SELECT $1, UNNEST($2::uuid[]), $3
+        ON CONFLICT (list_id, user_id) DO NOTHING RETURNING user_id",
+        id,
+        &user_ids,
+        current_user.id
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let title = sqlx::query_scalar!("SELECT title FROM lists WHERE id = $1", id)
+        .fetch_one(&data.db)
+        .await
+        .map_err(internal_error)?;
+    for user in candidates
+        .iter()
+        .filter(|user| assigned.contains(&user.id) && user.id != current_user.id)
+    {
+        let notification = NewNotification {
+            user_id: user.id,
+            kind: NotificationType::Assigned,
+            actor_id: Some(current_user.id),
+            list_id: Some(id),
+            project_id: None,
+            comment_id: None,
+            message: format!("{} assigned you to \"{}\"", current_user.username, title),
+            dedupe_key: None,
+        };
+        if notify(&data.db, notification)
+            .await
+            .map_err(internal_error)?
+        {
+            send_assignment_mail(&user.email, &current_user.username, &title).await;
+        }
+    }
+
+    get_assignees(&id, &data.db).await
+}
+
+/// Editors can take anyone off an item; everyone else can only unassign themselves.
+pub async fn unassign_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let min_role = if user_id == current_user.id {
+        ShareRole::Viewer
+    } else {
+        ShareRole::Editor
+    };
+    require_list_role(&id, &current_user.id, min_role, &data.db).await?;
+
+    match sqlx::query!(
+        "DELETE FROM list_assignees WHERE list_id = $1 AND user_id = $2",
+        id,
+        user_id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(result) if result.rows_affected() == 1 => Ok(Json(
+            json!({"status": "success", "message": "Assignee removed"}),
+        )),
+        Ok(_) => {
+            let error_response =
+                json!({"status": "fail", "message": "This item is not assigned to that user"});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot remove assignee: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+async fn get_assignees(
+    id: &Uuid,
+    pool: &PgPool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        ListAssigneeModel,
+        "SELECT a.user_id, u.username, u.email, a.assigned_by, a.created_at
+        FROM list_assignees a JOIN users u ON u.id = a.user_id WHERE a.list_id = $1 ORDER BY a.created_at, u.username",
+        id
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(assignees) => Ok(Json(
+            json!({"status": "success", "data": {"assignees": assignees}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch assignees: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn load_list_assignees(
+    lists: &mut [ListModel],
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    if lists.is_empty() {
+        return Ok(());
+    }
+
+    let list_ids: Vec<Uuid> = lists.iter().map(|list| list.id).collect();
+
+    let rows = sqlx::query!(
+        "SELECT a.list_id, a.user_id, u.username, u.email, a.assigned_by, a.created_at
+        FROM list_assignees a JOIN users u ON u.id = a.user_id
+        WHERE a.list_id = ANY($1) ORDER BY a.created_at, u.username",
+        &list_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut assignees_by_list: HashMap<Uuid, Vec<ListAssigneeModel>> = HashMap::new();
+    for row in rows {
+        assignees_by_list
+            .entry(row.list_id)
+            .or_default()
+            .push(ListAssigneeModel {
+                user_id: row.user_id,
+                username: row.username,
+                email: row.email,
+                assigned_by: row.assigned_by,
+                created_at: row.created_at,
+            });
+    }
+
+    for list in lists.iter_mut() {
+        list.assignees = assignees_by_list.remove(&list.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use crate::{
+    config::Config,
+    handlers::require_list_role,
+    models::{AttachmentModel, ShareRole, UserModel},
+    AppState,
+};
+use axum::{
+    body::Bytes,
+    extract::{Extension, Json, Multipart, Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+        StatusCode,
+    },
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// How many files a single upload request may carry.
+const MAX_FILES_PER_UPLOAD: usize = 5;
+
+/// Room for a full batch of files plus the multipart framing around them.
+pub fn upload_body_limit(config: &Config) -> usize {
+    config.max_attachment_size * MAX_FILES_PER_UPLOAD + 64 * 1024
+}
+
+struct PendingFile {
+    filename: String,
+    content_type: String,
+    data: Bytes,
+}
+
+/// Accepts one or more `file` fields. Every file is checked before any of them is stored, so a
+/// request either adds all its files or none.
+pub async fn upload_attachments_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Editor, &data.db).await?;
+
+    let bad_request = |message: String| {
+        let error_response = json!({"status": "fail", "message": message});
+        (StatusCode::BAD_REQUEST, Json(error_response))
+    };
+    let too_large = || {
+        let error_response = json!({"status": "fail", "message": format!("Attachments cannot be larger than {} bytes", data.config.max_attachment_size)});
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(error_response))
+    };
+
+    let mut files: Vec<PendingFile> = Vec::new();
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| bad_request(err.body_text()))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        if files.len() == MAX_FILES_PER_UPLOAD {
+            return Err(bad_request(format!(
+                "At most {} files can be uploaded at once",
+                MAX_FILES_PER_UPLOAD
+            )));
+        }
+
+        let filename = clean_filename(field.file_name().unwrap_or_default());
+        let declared_type = field.content_type().map(str::to_lowercase);
+
+        let mut content: Vec<u8> = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|err| match err.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => too_large(),
+            _ => bad_request(err.body_text()),
+        })? {
+            if content.len() + chunk.len() > data.config.max_attachment_size {
+                return Err(too_large());
+            }
+            content.extend_from_slice(&chunk);
+        }
+        if content.is_empty() {
+            return Err(bad_request(format!("'{}' is empty", filename)));
+        }
+
+        // Trust the file's signature over whatever the client claims, when there is one
+        let content_type = infer::get(&content)
+            .map(|kind| kind.mime_type().to_string())
+            .or(declared_type)
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        if !data
+            .config
+            .allowed_attachment_types
+            .iter()
+            .any(|allowed| allowed == essence)
+        {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("'{}' has type {}, which is not allowed", filename, essence),
+                "data": {"allowedTypes": data.config.allowed_attachment_types}
+            });
+            return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(error_response)));
+        }
+
+        files.push(PendingFile {
+            filename,
+            content_type,
+            data: content.into(),
+        });
+    }
+    if files.is_empty() {
+        return Err(bad_request(
+            "Send the files to attach as multipart 'file' fields".to_string(),
+        ));
+    }
+
+    let mut attachments: Vec<AttachmentModel> = Vec::with_capacity(files.len());
+    for file in files {
+        match store_attachment(&data, &current_user.id, &id, file).await {
+            Ok(attachment) => attachments.push(attachment),
+            Err(message) => {
+                // Don't leave the earlier files of this request behind
+                for attachment in &attachments {
+                    let _ = sqlx::query!("DELETE FROM attachments WHERE id = $1", attachment.id)
+                        .execute(&data.db)
+                        .await;
+                }
+                let error_response = json!({"status": "fail", "message": message});
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            }
+        }
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"attachments": attachments}})),
+    ))
+}
+
+/// Uploads the file, then records it; the file is removed again if recording fails.
+async fn store_attachment(
+    data: &AppState,
+    user_id: &Uuid,
+    list_id: &Uuid,
+    file: PendingFile,
+) -> Result<AttachmentModel, String> {
+    let attachment_id = Uuid::new_v4();
+    let storage_key = format!("attachments/{}/{}", list_id, attachment_id);
+    let size = file.data.len() as i64;
+
+    data.storage
+        .put(&storage_key, &file.content_type, file.data)
+        .await
+        .map_err(|err| format!("Cannot store '{}': {}", file.filename, err))?;
+
+    let insert_result = sqlx::query_as!(
+        AttachmentModel,
+        "INSERT INTO attachments (id, list_id, user_id, filename, content_type, size_bytes, storage_key)
+        VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        attachment_id,
+        list_id,
+        user_id,
+        file.filename,
+        file.content_type,
+        size,
+        storage_key
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match insert_result {
+        Ok(attachment) => Ok(attachment),
+        Err(err) => {
+            let _ = data.storage.delete(&storage_key).await;
+            Err(format!("Cannot save '{}': {:?}", file.filename, err))
+        }
+    }
+}
+
+pub async fn get_attachments_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    match sqlx::query_as!(
+        AttachmentModel,
+        "SELECT * FROM attachments WHERE list_id = $1 ORDER BY created_at, id",
+        id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(attachments) => Ok(Json(
+            json!({"status": "success", "data": {"attachments": attachments}}),
+        )),
+        Err(err) => {
+            let error_response = json!({"status": "fail", "message": format!("Cannot fetch attachments: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Always served as a download with sniffing turned off, so an uploaded file can't run as a page
+/// on the API's origin.
+pub async fn download_attachment_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+    let attachment = get_attachment(&id, &attachment_id, &data.db).await?;
+
+    let content = data.storage.get(&attachment.storage_key).await.map_err(|err| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot read this attachment: {}", err)});
+        (StatusCode::BAD_GATEWAY, Json(error_response))
+    })?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, attachment.content_type.clone()),
+            (
+                CONTENT_DISPOSITION,
+                content_disposition(&attachment.filename),
+            ),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        content,
+    ))
+}
+
+pub async fn delete_attachment_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Editor, &data.db).await?;
+
+    let deleted = sqlx::query_scalar!(
+        "DELETE FROM attachments WHERE id = $1 AND list_id = $2 RETURNING storage_key",
+        attachment_id,
+        id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|err| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot delete this attachment: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?;
+
+    let Some(storage_key) = deleted else {
+        let error_response = json!({"status": "fail", "message": "Attachment not found"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    // The file was queued for removal by the delete; if storage is unreachable now, the
+    // orphaned files job gets it later
+    if data.storage.delete(&storage_key).await.is_ok() {
+        let _ = sqlx::query!(
+            "DELETE FROM orphaned_files WHERE storage_key = $1",
+            storage_key
+        )
+        .execute(&data.db)
+        .await;
+    }
+
+    Ok(Json(
+        json!({"status": "success", "message": "Attachment deleted"}),
+    ))
+}
+
+async fn get_attachment(
+    list_id: &Uuid,
+    attachment_id: &Uuid,
+    pool: &PgPool,
+) -> Result<AttachmentModel, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as!(
+        AttachmentModel,
+        "SELECT * FROM attachments WHERE id = $1 AND list_id = $2",
+        attachment_id,
+        list_id
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+    .ok_or_else(|| {
+        let error_response = json!({"status": "fail", "message": "Attachment not found"});
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// Keeps the last path segment of a client-supplied name, without control characters.
+fn clean_filename(name: &str) -> String {
+    let name: String = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(255)
+        .collect();
+    match name.trim() {
+        "" | "." | ".." => "file".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// `attachment` with an ASCII fallback name plus the exact UTF-8 name (RFC 6266).
+fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded: String = filename
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback, encoded
+    )
+}
@@ -0,0 +1,342 @@
+use crate::{
+    config::Config,
+    handlers::begin_with_actor,
+    models::{Importance, ListStatus, ShareRole, UserModel},
+    schemas::{BulkItemResult, BulkItemStatus, BulkListSchema, BulkMode, BulkOperation},
+    utils::{list_constraint_message, validate_list_text},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::{Acquire, PgConnection, Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const MAX_BULK_ITEMS: usize = 500;
+
+/// Runs every operation inside one transaction, giving each item its own savepoint so a
+/// failure can be undone on its own in best-effort mode.
+pub async fn bulk_lists_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<BulkListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let item_count: usize = body.operations.iter().map(|op| op.ids().len()).sum();
+    if item_count == 0 {
+        let error_response =
+            json!({"status": "fail", "message": "Provide at least one operation with ids"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    if item_count > MAX_BULK_ITEMS {
+        let error_response = json!({"status": "fail", "message": format!("A bulk request can touch at most {} items", MAX_BULK_ITEMS)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot apply bulk operations: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let atomic = body.mode == BulkMode::Atomic;
+    let mut tx = begin_with_actor(&data.db, &current_user.id)
+        .await
+        .map_err(internal_error)?;
+    let mut results: Vec<BulkItemResult> = Vec::with_capacity(item_count);
+    let mut failed = false;
+
+    for (index, operation) in body.operations.iter().enumerate() {
+        let precheck = if failed && atomic {
+            Ok(())
+        } else {
+            check_operation(&mut tx, &data.config, &current_user.id, operation)
+                .await
+                .map_err(internal_error)?
+        };
+
+        for id in operation.ids() {
+            let (status, message) = if failed && atomic {
+                (BulkItemStatus::Skipped, None)
+            } else {
+                let outcome = match &precheck {
+                    Ok(()) => apply_in_savepoint(&mut tx, &current_user.id, id, operation).await,
+                    Err(message) => Err(message.clone()),
+                };
+                match outcome {
+                    Ok(()) => (BulkItemStatus::Ok, None),
+                    Err(message) => {
+                        failed = true;
+                        (BulkItemStatus::Failed, Some(message))
+                    }
+                }
+            };
+
+            results.push(BulkItemResult {
+                operation: index,
+                op: operation.name(),
+                id: *id,
+                status,
+                message,
+            });
+        }
+    }
+
+    if failed && atomic {
+        tx.rollback().await.map_err(internal_error)?;
+        for result in results.iter_mut() {
+            if result.status == BulkItemStatus::Ok {
+                result.status = BulkItemStatus::RolledBack;
+            }
+        }
+
+        let error_response = json!({
+            "status": "fail",
+            "message": "No changes were made because at least one item failed",
+            "data": {"results": results}
+        });
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+
+    tx.commit().await.map_err(internal_error)?;
+
+    let succeeded = results
+        .iter()
+        .filter(|result| result.status == BulkItemStatus::Ok)
+        .count();
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "succeeded": succeeded,
+            "failed": results.len() - succeeded,
+            "results": results
+        }
+    })))
+}
+
+/// Validates what an operation refers to besides the items themselves, once per operation.
+async fn check_operation(
+    tx: &mut Transaction<'_, Postgres>,
+    config: &Config,
+    user_id: &Uuid,
+    operation: &BulkOperation,
+) -> Result<Result<(), String>, sqlx::Error> {
+    match operation {
+        BulkOperation::Update { ids, fields } => {
+            if let Err(message) = validate_list_text(
+                config,
+                fields.title.as_deref(),
+                fields.descr.as_deref(),
+                fields.body.as_deref(),
+            ) {
+                return Ok(Err(message));
+            }
+            if fields.title.is_some() && ids.len() > 1 {
+                return Ok(Err(
+                    "A title can only be set on one item at a time".to_string()
+                ));
+            }
+        }
+        BulkOperation::MoveToProject {
+            project_id: Some(project_id),
+            ..
+        } => {
+            let exists = sqlx::query_scalar!(
+                "SELECT EXISTS(SELECT 1 FROM projects WHERE id = $1 AND project_role(id, $2) >= 'editor')",
+                project_id,
+                user_id
+            )
+            .fetch_one(&mut **tx)
+            .await?
+            .unwrap_or(false);
+            if !exists {
+                return Ok(Err("Project not found".to_string()));
+            }
+        }
+        BulkOperation::Tag { tag_ids, .. } | BulkOperation::Untag { tag_ids, .. } => {
+            let owned = sqlx::query_scalar!(
+                "SELECT COUNT(*) FROM tags WHERE id = ANY($1) AND user_id = $2",
+                tag_ids,
+                user_id
+            )
+            .fetch_one(&mut **tx)
+            .await?
+            .unwrap_or(0);
+            let mut unique_ids = tag_ids.clone();
+            unique_ids.sort();
+            unique_ids.dedup();
+            if tag_ids.is_empty() || owned != unique_ids.len() as i64 {
+                return Ok(Err("One or more tags were not found".to_string()));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(Ok(()))
+}
+
+async fn apply_in_savepoint(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    id: &Uuid,
+    operation: &BulkOperation,
+) -> Result<(), String> {
+    let database_error = |err: sqlx::Error| format!("Database error: {}", err);
+
+    let mut savepoint = tx.begin().await.map_err(database_error)?;
+    match apply_operation(&mut savepoint, user_id, id, operation).await {
+        Ok(()) => savepoint.commit().await.map_err(database_error),
+        Err(message) => {
+            savepoint.rollback().await.map_err(database_error)?;
+            Err(message)
+        }
+    }
+}
+
+async fn apply_operation(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    id: &Uuid,
+    operation: &BulkOperation,
+) -> Result<(), String> {
+    let database_error = |err: sqlx::Error| {
+        list_constraint_message(&err).unwrap_or_else(|| format!("Database error: {}", err))
+    };
+
+    ensure_list_role(conn, user_id, id, required_role(operation)).await?;
+
+    let result = match operation {
+        BulkOperation::Update { fields, .. } => {
+            // Titles are unique among the items of whoever created this one
+            if let Some(title) = &fields.title {
+                let taken = sqlx::query_scalar!(
+                    "SELECT EXISTS(SELECT 1 FROM lists WHERE title = $1 AND user_id = (SELECT user_id FROM lists WHERE id = $2) AND id <> $2 AND deleted_at IS NULL)",
+                    title,
+                    id
+                )
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(database_error)?
+                .unwrap_or(false);
+                if taken {
+                    return Err("You already have an item with this title".to_string());
+                }
+            }
+
+            sqlx::query!(
+                "UPDATE lists SET title = COALESCE($1, title), descr = COALESCE($2, descr), body = COALESCE($3, body),
+                importance = COALESCE($4, importance), status = COALESCE($5, status), due_at = COALESCE($6, due_at),
+                completed_at = CASE WHEN COALESCE($5, status) <> 'done' THEN NULL WHEN status = 'done' THEN completed_at ELSE NOW() END,
+                updated_at = NOW()
+                WHERE id = $7 AND deleted_at IS NULL",
+                fields.title,
+                fields.descr,
+                fields.body,
+                fields.importance as Option<Importance>,
+                fields.status as Option<ListStatus>,
+                fields.due_at,
+                id
+            )
+            .execute(&mut *conn)
+            .await
+        }
+        BulkOperation::Complete { .. } => {
+            sqlx::query!(
+                "UPDATE lists SET status = 'done', completed_at = CASE WHEN status = 'done' THEN completed_at ELSE NOW() END, updated_at = NOW()
+                WHERE id = $1 AND deleted_at IS NULL",
+                id
+            )
+            .execute(&mut *conn)
+            .await
+        }
+        BulkOperation::MoveToProject { project_id, .. } => {
+            sqlx::query!(
+                "UPDATE lists SET project_id = $1, updated_at = NOW() WHERE id = $2 AND deleted_at IS NULL",
+                *project_id,
+                id
+            )
+            .execute(&mut *conn)
+            .await
+        }
+        BulkOperation::Tag { tag_ids, .. } => {
+            sqlx::query!(
+                "INSERT INTO list_tags (list_id, tag_id) SELECT $1, UNNEST($2::uuid[]) ON CONFLICT DO NOTHING",
+                id,
+                tag_ids
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(database_error)?;
+            return Ok(());
+        }
+        BulkOperation::Untag { tag_ids, .. } => {
+            sqlx::query!(
+                "DELETE FROM list_tags WHERE list_id = $1 AND tag_id = ANY($2)",
+                id,
+                tag_ids
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(database_error)?;
+            return Ok(());
+        }
+        BulkOperation::Archive { .. } => {
+            sqlx::query!(
+                "UPDATE lists SET archived_at = COALESCE(archived_at, NOW()), updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+                id
+            )
+            .execute(&mut *conn)
+            .await
+        }
+        BulkOperation::Delete { .. } => {
+            sqlx::query!(
+                "UPDATE lists SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+                id
+            )
+            .execute(&mut *conn)
+            .await
+        }
+    };
+
+    match result.map_err(database_error)?.rows_affected() {
+        0 => Err("List not found".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Deleting an item and moving it to another project take owner access, everything else editor access.
+fn required_role(operation: &BulkOperation) -> ShareRole {
+    match operation {
+        BulkOperation::Delete { .. } | BulkOperation::MoveToProject { .. } => ShareRole::Owner,
+        _ => ShareRole::Editor,
+    }
+}
+
+async fn ensure_list_role(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    id: &Uuid,
+    min_role: ShareRole,
+) -> Result<(), String> {
+    let role = sqlx::query_scalar!(
+        r#"SELECT list_role($1, $2) AS "role: ShareRole" FROM lists WHERE id = $1 AND deleted_at IS NULL"#,
+        id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| format!("Database error: {}", err))?
+    .flatten();
+
+    match role {
+        Some(role) if role >= min_role => Ok(()),
+        Some(_) => Err(format!(
+            "You need {} access to this item",
+            min_role.as_str()
+        )),
+        None => Err("List not found".to_string()),
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    handlers::{find_user_by_username, notify, require_list_role, NewNotification},
+    models::{CommentModel, NotificationType, ShareRole, UserModel},
+    schemas::{CommentQuery, CreateCommentSchema, UpdateCommentSchema},
+    utils::{parse_mentions, parse_render, send_mention_mail, MAX_MENTIONS},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Keeps the page's OFFSET within a Postgres BIGINT.
+const MAX_PAGE: usize = i64::MAX as usize / MAX_PAGE_SIZE;
+
+/// Threads oldest first, each with all of its replies. Deleted comments that still have replies
+/// stay in place with an empty body.
+pub async fn get_comments_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CommentQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let page = params.page.unwrap_or(1);
+    let page_size = params.page_size.unwrap_or(20);
+    if !(1..=MAX_PAGE).contains(&page) || !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        let error_response = json!({"status": "fail", "message": format!("'page' must be between 1 and {} and 'page_size' between 1 and {}", MAX_PAGE, MAX_PAGE_SIZE)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    let render_html = parse_render(params.render.as_deref()).map_err(|message| {
+        let error_response = json!({"status": "fail", "message": message});
+        (StatusCode::BAD_REQUEST, Json(error_response))
+    })?;
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch comments: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let total_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM list_comments WHERE list_id = $1 AND parent_id IS NULL"#,
+        id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let mut threads = sqlx::query_as::<_, CommentModel>(
+        "SELECT c.*, u.username FROM list_comments c LEFT JOIN users u ON u.id = c.user_id
+        WHERE c.list_id = $1 AND c.parent_id IS NULL
+        ORDER BY c.created_at, c.id LIMIT $2 OFFSET $3",
+    )
+    .bind(id)
+    .bind(page_size as i64)
+    .bind(((page - 1) * page_size) as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let thread_ids: Vec<Uuid> = threads.iter().map(|thread| thread.id).collect();
+    let replies = sqlx::query_as::<_, CommentModel>(
+        "SELECT c.*, u.username FROM list_comments c LEFT JOIN users u ON u.id = c.user_id
+        WHERE c.parent_id = ANY($1) ORDER BY c.created_at, c.id",
+    )
+    .bind(&thread_ids)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let mut replies_by_thread: HashMap<Uuid, Vec<CommentModel>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_id) = reply.parent_id {
+            replies_by_thread.entry(parent_id).or_default().push(reply);
+        }
+    }
+    for thread in threads.iter_mut() {
+        thread.replies = Some(replies_by_thread.remove(&thread.id).unwrap_or_default());
+        if render_html {
+            thread.render_body();
+        }
+    }
+
+    let has_more = (page * page_size) < total_count as usize;
+    let next_page = if has_more { Some(page + 1) } else { None };
+    let prev_page = if page > 1 { Some(page - 1) } else { None };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "comments": threads,
+            "hasMore": has_more,
+            "nextPage": next_page,
+            "prevPage": prev_page,
+            "totalCount": total_count
+        }
+    })))
+}
+
+/// Anyone who can see an item can comment on it. People mentioned with `@username` who can see
+/// the item are notified.
+pub async fn add_comment_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<CreateCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let content = validate_comment(&body.body, data.config.max_comment_length)?;
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot add comment: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let parent_id = match body.parent_id {
+        Some(parent_id) => {
+            let parent = sqlx::query!(
+                "SELECT id, parent_id FROM list_comments WHERE id = $1 AND list_id = $2",
+                parent_id,
+                id
+            )
+            .fetch_optional(&data.db)
+            .await
+            .map_err(internal_error)?;
+            let Some(parent) = parent else {
+                return Err(comment_not_found());
+            };
+            Some(parent.parent_id.unwrap_or(parent.id))
+        }
+        None => None,
+    };
+
+    let comment_id = sqlx::query_scalar!(
+        "INSERT INTO list_comments (list_id, user_id, parent_id, body) VALUES ($1, $2, $3, $4) RETURNING id",
+        id,
+        current_user.id,
+        parent_id,
+        content
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let comment = get_comment(&id, &comment_id, &data.db).await?;
+    notify_mentions(&comment, &current_user, &data.db)
+        .await
+        .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"comment": comment}})),
+    ))
+}
+
+/// Only the author can edit a comment. Only people who weren't mentioned in it before are notified.
+pub async fn update_comment_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let content = validate_comment(&body.body, data.config.max_comment_length)?;
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    let comment = get_comment(&id, &comment_id, &data.db).await?;
+    if comment.deleted_at.is_some() {
+        return Err(comment_not_found());
+    }
+    if comment.user_id != Some(current_user.id) {
+        let error_response =
+            json!({"status": "fail", "message": "Only the author can edit a comment"});
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot update comment: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    sqlx::query!(
+        "UPDATE list_comments SET body = $1, edited_at = NOW() WHERE id = $2",
+        content,
+        comment_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let comment = get_comment(&id, &comment_id, &data.db).await?;
+    notify_mentions(&comment, &current_user, &data.db)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "data": {"comment": comment}}),
+    ))
+}
+
+/// Authors delete their own comments, item owners anyone's. A comment with replies is blanked
+/// out rather than removed, so the thread stays readable.
+pub async fn delete_comment_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let role = require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    let comment = get_comment(&id, &comment_id, &data.db).await?;
+    if comment.deleted_at.is_some() {
+        return Err(comment_not_found());
+    }
+    if comment.user_id != Some(current_user.id) && role < ShareRole::Owner {
+        let error_response =
+            json!({"status": "fail", "message": "You can only delete your own comments"});
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot delete comment: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    sqlx::query!(
+        "UPDATE list_comments SET body = '', deleted_at = NOW()
+        WHERE id = $1 AND EXISTS (SELECT 1 FROM list_comments r WHERE r.parent_id = $1)",
+        comment_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    sqlx::query!(
+        "DELETE FROM list_comments WHERE id = $1 AND deleted_at IS NULL",
+        comment_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    // The last reply of a deleted comment takes the blanked out comment with it
+    if let Some(parent_id) = comment.parent_id {
+        sqlx::query!(
+            "DELETE FROM list_comments WHERE id = $1 AND deleted_at IS NOT NULL
+            AND NOT EXISTS (SELECT 1 FROM list_comments r WHERE r.parent_id = $1)",
+            parent_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "message": "Comment deleted"}),
+    ))
+}
+
+async fn get_comment(
+    list_id: &Uuid,
+    comment_id: &Uuid,
+    pool: &PgPool,
+) -> Result<CommentModel, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as::<_, CommentModel>(
+        "SELECT c.*, u.username FROM list_comments c LEFT JOIN users u ON u.id = c.user_id
+        WHERE c.id = $1 AND c.list_id = $2",
+    )
+    .bind(comment_id)
+    .bind(list_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch comment: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?
+    .ok_or_else(comment_not_found)
+}
+
+/// Resolves the `@username` mentions of a comment and notifies everyone mentioned for the first
+/// time. Unknown usernames, the author and people who can't see the item are skipped.
+async fn notify_mentions(
+    comment: &CommentModel,
+    author: &UserModel,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    let usernames = parse_mentions(&comment.body);
+    if usernames.is_empty() {
+        return Ok(());
+    }
+
+    let title = sqlx::query_scalar!("SELECT title FROM lists WHERE id = $1", comment.list_id)
+        .fetch_one(pool)
+        .await?;
+
+    for username in usernames.iter().take(MAX_MENTIONS) {
+        let Some(user) = find_user_by_username(username, pool).await? else {
+            continue;
+        };
+        if user.id == author.id {
+            continue;
+        }
+
+        let newly_mentioned = sqlx::query_scalar!(
+            "INSERT INTO comment_mentions (comment_id, user_id)
+            SELECT $1, $2 WHERE list_role($3, $2) IS NOT NULL
+            ON CONFLICT DO NOTHING RETURNING user_id",
+            comment.id,
+            user.id,
+            comment.list_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+        if !newly_mentioned {
+            continue;
+        }
+
+        let notification = NewNotification {
+            user_id: user.id,
+            kind: NotificationType::Mentioned,
+            actor_id: Some(author.id),
+            list_id: Some(comment.list_id),
+            project_id: None,
+            comment_id: Some(comment.id),
+            message: format!("{} mentioned you on \"{}\"", author.username, title),
+            dedupe_key: None,
+        };
+        if notify(pool, notification).await? {
+            send_mention_mail(&user.email, &author.username, &title, &comment.body).await;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_comment(
+    body: &str,
+    max_length: usize,
+) -> Result<&str, (StatusCode, Json<serde_json::Value>)> {
+    let body = body.trim();
+    if body.is_empty() || body.chars().count() > max_length {
+        let error_response = json!({"status": "fail", "message": format!("A comment must be between 1 and {} characters", max_length)});
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)));
+    }
+    Ok(body)
+}
+
+fn comment_not_found() -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({"status": "fail", "message": "Comment not found"});
+    (StatusCode::NOT_FOUND, Json(error_response))
+}
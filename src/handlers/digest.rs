@@ -0,0 +1,125 @@
+use crate::{
+    models::{DigestFrequency, DigestModel, UserModel},
+    schemas::UpdateDigestSchema,
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// The current user's digest settings, or `null` when they haven't opted in.
+pub async fn get_digest_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        DigestModel,
+        r#"SELECT user_id, frequency AS "frequency: DigestFrequency", send_time, weekday, timezone,
+            next_send_at, last_sent_at, created_at, updated_at
+        FROM email_digests WHERE user_id = $1"#,
+        current_user.id
+    )
+    .fetch_optional(&data.db)
+    .await
+    {
+        Ok(digest) => Ok(Json(
+            json!({"status": "success", "data": {"digest": digest}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch digest: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Opts in to the digest or changes its schedule. The next one goes out at the first matching
+/// local time from now on.
+pub async fn update_digest_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<UpdateDigestSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let invalid = |message: &str| {
+        let error_response = json!({"status": "fail", "message": message});
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
+    };
+    let weekday = match (body.frequency, body.weekday) {
+        (DigestFrequency::Daily, _) => None,
+        (DigestFrequency::Weekly, Some(weekday)) if (1..=7).contains(&weekday) => Some(weekday),
+        (DigestFrequency::Weekly, _) => {
+            return Err(invalid(
+                "Weekly digests need a weekday from 1 (Monday) to 7 (Sunday)",
+            ))
+        }
+    };
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot update digest: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let timezone = body.timezone.trim();
+    let known_timezone = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1) AS "exists!""#,
+        timezone
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+    if !known_timezone {
+        return Err(invalid(
+            "Unknown timezone, use an IANA name such as Europe/Berlin",
+        ));
+    }
+
+    let digest = sqlx::query_as!(
+        DigestModel,
+        r#"INSERT INTO email_digests (user_id, frequency, send_time, weekday, timezone, next_send_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        ON CONFLICT (user_id) DO UPDATE SET frequency = EXCLUDED.frequency, send_time = EXCLUDED.send_time,
+            weekday = EXCLUDED.weekday, timezone = EXCLUDED.timezone
+        RETURNING user_id, frequency AS "frequency: DigestFrequency", send_time, weekday, timezone,
+            next_send_at, last_sent_at, created_at, updated_at"#,
+        current_user.id,
+        body.frequency as DigestFrequency,
+        body.send_time,
+        weekday,
+        timezone
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "data": {"digest": digest}}),
+    ))
+}
+
+/// Opts out of the digest.
+pub async fn delete_digest_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query!(
+        "DELETE FROM email_digests WHERE user_id = $1",
+        current_user.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(_) => Ok(Json(
+            json!({"status": "success", "message": "Digest turned off"}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot update digest: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    models::{ListChangeNotice, StreamUserGuard, UserModel},
+    schemas::EventStreamQuery,
+    utils::{sign_token, verify_token},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use chrono::{Duration, Utc};
+use futures_util::stream::{self, Stream};
+use serde_json::json;
+use std::{convert::Infallible, sync::Arc};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+const EVENT_STREAM_TOKEN: &str = "event_stream";
+
+/// How long a stream ticket can be used to connect.
+const TICKET_TTL_SECONDS: i64 = 60;
+
+/// Browsers' `EventSource` can't send an `Authorization` header, so streams are opened with a
+/// short-lived ticket in the query string instead of the long-lived JWT.
+pub async fn create_event_ticket_handler(
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let expires_at = Utc::now() + Duration::seconds(TICKET_TTL_SECONDS);
+    let ticket = sign_token(
+        EVENT_STREAM_TOKEN,
+        &(current_user.id, expires_at.timestamp()),
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"ticket": ticket, "expiresAt": expires_at}})),
+    ))
+}
+
+/// Server-Sent Events for changes to every item the user can see, named after the change
+/// (`list.updated`, `comment.created`, ...) with the item's id, workspace and new version. A
+/// `resync` event means changes may have been missed and the client should refetch.
+pub async fn event_stream_handler(
+    State(data): State<Arc<AppState>>,
+    Query(params): Query<EventStreamQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = params
+        .ticket
+        .as_deref()
+        .and_then(|ticket| verify_token::<(Uuid, i64)>(EVENT_STREAM_TOKEN, ticket.trim()))
+        .filter(|(_, expires_at)| *expires_at > Utc::now().timestamp())
+        .map(|(user_id, _)| user_id)
+        .ok_or_else(|| {
+            let error_response =
+                json!({"status": "fail", "message": "Provide a valid stream ticket"});
+            (StatusCode::UNAUTHORIZED, Json(error_response))
+        })?;
+
+    // Registered before subscribing, so every change the stream receives was checked for them
+    let guard = data.stream_users.register(user_id);
+    let stream = change_stream(data.changes.subscribe(), user_id, guard);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// `guard` lives as long as the stream, keeping the user registered until the client leaves.
+fn change_stream(
+    receiver: broadcast::Receiver<ListChangeNotice>,
+    user_id: Uuid,
+    guard: StreamUserGuard,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold((receiver, guard), move |(mut receiver, guard)| async move {
+        loop {
+            let change = match receiver.recv().await {
+                Ok(ListChangeNotice::Change(change, viewers)) if viewers.contains(&user_id) => {
+                    change
+                }
+                Ok(ListChangeNotice::Change(..)) => continue,
+                // Fell too far behind, or the listener reconnected
+                Ok(ListChangeNotice::Resync) | Err(RecvError::Lagged(_)) => {
+                    let event = Event::default().event("resync").data("{}");
+                    return Some((Ok(event), (receiver, guard)));
+                }
+                Err(RecvError::Closed) => return None,
+            };
+
+            let event = Event::default()
+                .event(&change.event)
+                .json_data(&change)
+                .unwrap_or_else(|_| Event::default().event("resync").data("{}"));
+            return Some((Ok(event), (receiver, guard)));
+        }
+    })
+}
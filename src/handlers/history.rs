@@ -0,0 +1,201 @@
+use crate::{
+    handlers::{get_list_by_title, get_list_tags, is_duplicate_title},
+    models::{Importance, ListHistoryModel, ListModel, ListRow, ListStatus, ShareRole, UserModel},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The content of an item as of one revision, rebuilt from its history.
+#[derive(Debug, Deserialize)]
+struct ListSnapshot {
+    title: String,
+    descr: Option<String>,
+    body: Option<String>,
+    importance: Option<Importance>,
+    status: Option<ListStatus>,
+    project_id: Option<Uuid>,
+    due_at: Option<chrono::DateTime<chrono::Utc>>,
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub async fn get_list_history_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    match sqlx::query_as!(
+        ListHistoryModel,
+        "SELECT * FROM list_history WHERE list_id = $1 ORDER BY created_at DESC, id DESC",
+        id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(history) => Ok(Json(
+            json!({"status": "success", "data": {"history": history}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch history: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Puts the item's content back the way it was right after the given revision. The revert is
+/// itself recorded as a new revision, so it can be undone the same way.
+pub async fn revert_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, revision_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let internal_error = |err: sqlx::Error| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot revert this list item: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let owner_id = ensure_list_role(&id, &current_user.id, ShareRole::Editor, &data.db).await?;
+
+    let snapshot = sqlx::query_scalar!(
+        r#"SELECT jsonb_object_agg(latest.key, latest.value) AS snapshot
+        FROM (
+            SELECT DISTINCT ON (f.key) f.key, f.value -> 'new' AS value
+            FROM list_history h, jsonb_each(h.changes) f
+            WHERE h.list_id = $1
+            AND (h.created_at, h.id) <= (SELECT created_at, id FROM list_history WHERE id = $2 AND list_id = $1)
+            ORDER BY f.key, h.created_at DESC, h.id DESC
+        ) latest"#,
+        id,
+        revision_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?
+    .and_then(|snapshot| serde_json::from_value::<ListSnapshot>(snapshot).ok());
+
+    let Some(snapshot) = snapshot else {
+        let error_response = json!({"status": "fail", "message": "Revision not found"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    if matches!(
+        get_list_by_title(&snapshot.title, &owner_id, &data.db).await,
+        Some(other) if other.id != id
+    ) {
+        let error_response =
+            json!({"status": "fail", "message": "You already have another item with this title"});
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let mut tx = begin_with_actor(&data.db, &current_user.id)
+        .await
+        .map_err(internal_error)?;
+    sqlx::query_scalar!("SELECT set_config('app.history_action', 'reverted', true)")
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    // A project deleted since then cannot be restored, the item simply stays without one
+    let query_result = sqlx::query_as!(
+        ListRow,
+        r#"UPDATE lists SET title = $1, descr = $2, body = $3, importance = COALESCE($4, importance), status = COALESCE($5, status),
+        project_id = (SELECT id FROM projects WHERE id = $6 AND workspace_id = lists.workspace_id AND project_role(id, $10) >= 'editor'), due_at = $7, completed_at = $8, updated_at = NOW()
+        WHERE id = $9 AND deleted_at IS NULL
+        RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+        snapshot.title,
+        snapshot.descr,
+        snapshot.body,
+        snapshot.importance as Option<Importance>,
+        snapshot.status as Option<ListStatus>,
+        snapshot.project_id,
+        snapshot.due_at,
+        snapshot.completed_at,
+        id,
+        current_user.id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        if is_duplicate_title(&err) {
+            let error_response =
+                json!({"status": "fail", "message": "You already have another item with this title"});
+            return (StatusCode::CONFLICT, Json(error_response));
+        }
+        internal_error(err)
+    })?;
+
+    let Some(mut list) = query_result.map(ListModel::from) else {
+        let error_response =
+            json!({"status": "fail", "message": "Restore this item from the trash first"});
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    };
+
+    tx.commit().await.map_err(internal_error)?;
+
+    list.tags = get_list_tags(&list.id, &data.db)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(json!({"status": "success", "data": {"list": list}})))
+}
+
+/// Starts a transaction whose changes to list items are recorded in their history as made by `actor_id`.
+pub async fn begin_with_actor(
+    pool: &PgPool,
+    actor_id: &Uuid,
+) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    set_history_actor(&mut tx, actor_id).await?;
+    Ok(tx)
+}
+
+async fn set_history_actor(conn: &mut PgConnection, actor_id: &Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT set_config('app.actor_id', $1, true)",
+        actor_id.to_string()
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Like `require_list_role`, but also covers items in the trash. Returns the id of the item's creator.
+async fn ensure_list_role(
+    id: &Uuid,
+    user_id: &Uuid,
+    min_role: ShareRole,
+    pool: &PgPool,
+) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+    let access = sqlx::query!(
+        r#"SELECT user_id, list_role(id, $2) AS "role: ShareRole" FROM lists WHERE id = $1"#,
+        id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+
+    match access {
+        Some(access) if access.role >= Some(min_role) => Ok(access.user_id.unwrap_or(*user_id)),
+        Some(access) if access.role.is_some() => {
+            let error_response = json!({"status": "fail", "message": format!("You need {} access to this item to do that", min_role.as_str())});
+            Err((StatusCode::FORBIDDEN, Json(error_response)))
+        }
+        _ => {
+            let error_response = json!({"status": "fail", "message": "List not found"});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+    }
+}
@@ -1,26 +1,48 @@
 use crate::{
-    models::{ListModel, UserModel},
-    schemas::{CreateListSchema, ListResponse, PaginationSchema, UpdateListSchema},
+    handlers::{
+        begin_with_actor, load_list_assignees, load_list_tags, require_list_role,
+        require_project_role, require_workspace_role,
+    },
+    models::{
+        CurrentWorkspace, Importance, ListModel, ListRow, ListStatus, ShareRole, TagModel,
+        UserModel, WorkspaceRole,
+    },
+    schemas::{CreateListSchema, ListResponse, MoveListSchema, UpdateListSchema},
+    utils::{
+        if_match_versions, list_constraint_message, list_etag, parse_render, validate_list_text,
+        CursorDirection, ListQuery,
+    },
     AppState,
 };
 use axum::{
     extract::{Extension, Json, Path, Query, State},
-    http::StatusCode,
+    http::{header::ETAG, HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use axum_macros::debug_handler;
 use chrono::Utc;
 use serde_json::json;
-use sqlx::{query, PgPool};
-use std::sync::Arc;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use std::{collections::HashMap, sync::Arc};
 use uuid::Uuid;
 
 #[debug_handler]
+/// Adds the item to the selected workspace.
 pub async fn add_list_handler(
     State(data): State<Arc<AppState>>,
     Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
     Json(body): Json<CreateListSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Member)?;
+    validate_list_text(
+        &data.config,
+        Some(&body.title),
+        body.descr.as_deref(),
+        body.body.as_deref(),
+    )
+    .map_err(invalid_list)?;
+
     let list = get_list_by_title(&body.title, &current_user.id, &data.db).await;
 
     match list {
@@ -30,41 +52,60 @@ pub async fn add_list_handler(
             Err((StatusCode::NOT_ACCEPTABLE, Json(error_response)))
         }
         None => {
+            if let Some(project_id) = body.project_id {
+                require_project_role(&project_id, &current_user.id, ShareRole::Editor, &data.db)
+                    .await?;
+            }
+
             let descr = body.descr.unwrap_or_default();
             let body_content = body.body.unwrap_or_default();
-            let importance = body.importance.to_string();
+            let status = body.status.unwrap_or_default();
+            let completed_at = (status == ListStatus::Done).then(Utc::now);
 
-            let query_result = sqlx::query!(
-                "INSERT INTO lists (user_id, title, descr, body, importance) VALUES ($1, $2, $3, $4, $5) RETURNING id, user_id, title, descr, body, importance, created_at, updated_at",
+            let internal_error = |err: sqlx::Error| {
+                if let Some(message) = list_constraint_message(&err) {
+                    return invalid_list(message);
+                }
+                let error_response =
+                    json!({"status": "fail", "message": format!("Cannot add item: {:?}", err)});
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+            };
+            let mut tx = begin_with_actor(&data.db, &current_user.id)
+                .await
+                .map_err(internal_error)?;
+
+            let query_result = sqlx::query_as!(
+                ListRow,
+                r#"INSERT INTO lists (user_id, title, descr, body, importance, status, project_id, due_at, completed_at, workspace_id, position)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, (SELECT COALESCE(MAX(position), 0) + 1024 FROM lists WHERE user_id = $1))
+                RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
                 current_user.id,
                 body.title,
                 descr,
                 body_content,
-                importance
+                body.importance as Importance,
+                status as ListStatus,
+                body.project_id,
+                body.due_at,
+                completed_at,
+                workspace.id
             )
-            .fetch_one(&data.db)
+            .fetch_one(&mut *tx)
             .await;
 
             match query_result {
-                Ok(row) => {
-                    let list = ListModel {
-                        id: row.id,
-                        user_id: row.user_id.unwrap(),
-                        title: row.title,
-                        descr: row.descr,
-                        body: row.body,
-                        importance: row.importance.unwrap(),
-                        created_at: row.created_at,
-                        updated_at: row.updated_at,
-                    };
+                Ok(list) => {
+                    tx.commit().await.map_err(internal_error)?;
+                    let list = ListModel::from(list);
                     let list_response = json!({"status": "success", "data": {"list": list}});
                     Ok(Json(list_response))
                 }
-                Err(err) => {
-                    let error_response =
-                        json!({"status": "fail", "message": format!("Cannot add item: {:?}", err)});
-                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+                // Lost a race against a concurrent request creating the same title
+                Err(err) if is_duplicate_title(&err) => {
+                    let error_response = json!({"status": "fail", "message": "You already have an item with this title"});
+                    Err((StatusCode::NOT_ACCEPTABLE, Json(error_response)))
                 }
+                Err(err) => Err(internal_error(err)),
             }
         }
     }
@@ -72,26 +113,45 @@ pub async fn add_list_handler(
 
 pub async fn get_list_by_id_handler(
     State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
     Path(id): Path<Uuid>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    match sqlx::query!("SELECT * FROM lists WHERE id = $1", id)
-        .fetch_one(&data.db)
-        .await
+    let render_html =
+        parse_render(params.get("render").map(String::as_str)).map_err(|message| {
+            let error_response = json!({"status": "fail", "message": message});
+            (StatusCode::BAD_REQUEST, Json(error_response))
+        })?;
+    let role = require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    match sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = $1 AND deleted_at IS NULL"#,
+        id
+    )
+    .fetch_one(&data.db)
+    .await
     {
-        Ok(row) => {
-            let list = ListModel {
-                id: row.id,
-                user_id: row.user_id.unwrap(),
-                title: row.title,
-                descr: row.descr,
-                body: row.body,
-                importance: row.importance.unwrap(),
-                created_at: row.created_at,
-                updated_at: row.updated_at,
+        Ok(list) => {
+            let mut list = ListModel::from(list);
+            list.tags = match get_list_tags(&list.id, &data.db).await {
+                Ok(tags) => tags,
+                Err(err) => {
+                    let error_response = json!({"status": "fail", "message": format!("Cannot fetch tags: {:?}", err)});
+                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+                }
             };
+            if let Err(err) = load_list_assignees(std::slice::from_mut(&mut list), &data.db).await {
+                let error_response = json!({"status": "fail", "message": format!("Cannot fetch assignees: {:?}", err)});
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            }
+            if render_html {
+                list.render_body();
+            }
 
-            let list_response = json!({"status": "success", "data": {"list": list}});
-            Ok(Json(list_response))
+            let etag = list_etag(list.version);
+            let list_response = json!({"status": "success", "data": {"list": list, "role": role}});
+            Ok(([(ETAG, etag)], Json(list_response)))
         }
         Err(_) => {
             let error_response = serde_json::json!({"status": "fail", "message": format!("Cannot retrieve this list item")});
@@ -104,53 +164,77 @@ pub async fn get_list_by_id_handler(
 pub async fn get_users_lists_handler(
     State(data): State<Arc<AppState>>,
     Extension(current_user): Extension<UserModel>,
-    Query(pagination): Query<PaginationSchema>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let page = pagination.page.unwrap_or(1);
-    let page_size = (pagination.page_size.unwrap_or(10));
-    let offset = ((page - 1) * page_size) as i64;
+    let mut list_query = ListQuery::parse(&params).map_err(|message| {
+        let error_response = json!({"status": "fail", "message": message});
+        (StatusCode::BAD_REQUEST, Json(error_response))
+    })?;
+    let page = list_query.page;
+    let page_size = list_query.page_size;
 
-    let search_title = pagination.search_title.unwrap_or_default();
-    let search_pattern = format!("%{}%", search_title);
+    let mut query_result = fetch_lists(&list_query, &current_user.id, &workspace, &data.db).await;
+    if matches!(&query_result, Ok((_, lists)) if lists.is_empty())
+        && list_query.fall_back_to_fuzzy()
+    {
+        query_result = fetch_lists(&list_query, &current_user.id, &workspace, &data.db).await;
+    }
 
-    let total_count = sqlx::query!(
-        "SELECT COUNT(*) FROM lists WHERE user_id = $1 AND title ILIKE $2",
-        current_user.id,
-        search_pattern
-    )
-    .fetch_one(&data.db)
-    .await
-    .map(|row| row.count)
-    .unwrap_or(Some(0));
+    match query_result {
+        Ok((total_count, mut lists)) => {
+            let has_extra_row = lists.len() > page_size;
+            lists.truncate(page_size);
+            if list_query.is_reversed() {
+                lists.reverse();
+            }
 
-    let has_more = total_count > Some(offset + page_size as i64);
-    let next_page = if has_more { Some(page + 1) } else { None };
-    let prev_page = if page > 1 { Some(page - 1) } else { None };
+            if let Err(err) = load_list_tags(&mut lists, &data.db).await {
+                let error_response =
+                    json!({"status": "fail", "message": format!("Cannot fetch tags: {:?}", err)});
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            }
+            if let Err(err) = load_list_assignees(&mut lists, &data.db).await {
+                let error_response = json!({"status": "fail", "message": format!("Cannot fetch assignees: {:?}", err)});
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            }
+            if list_query.render_html {
+                lists.iter_mut().for_each(ListModel::render_body);
+            }
 
-    match sqlx::query!(
-        "SELECT * FROM lists WHERE user_id = $1 AND title ILIKE $2 LIMIT $3 OFFSET $4",
-        current_user.id,
-        search_pattern,
-        page_size as i64,
-        offset
-    )
-    .fetch_all(&data.db)
-    .await
-    {
-        Ok(rows) => {
-            let lists: Vec<ListModel> = rows
-                .into_iter()
-                .map(|row| ListModel {
-                    id: row.id,
-                    title: row.title,
-                    user_id: row.user_id.unwrap(),
-                    descr: row.descr,
-                    body: row.body,
-                    importance: row.importance.unwrap(),
-                    created_at: row.created_at,
-                    updated_at: row.updated_at,
-                })
-                .collect();
+            if !list_query.cursor_mode {
+                let has_more = has_extra_row;
+                let next_page = if has_more { Some(page + 1) } else { None };
+                let prev_page = if page > 1 { Some(page - 1) } else { None };
+
+                let response = serde_json::json!({
+                "status": "success",
+                "data":
+                {
+                    "lists": lists,
+                    "hasMore": has_more,
+                    "nextPage": next_page,
+                    "prevPage": prev_page,
+                    "totalCount": total_count,
+                    "searchMode": list_query.search_mode()
+                }});
+
+                return Ok(Json(response));
+            }
+
+            let (has_more, has_previous) = if list_query.is_reversed() {
+                (true, has_extra_row)
+            } else {
+                (has_extra_row, list_query.cursor.is_some())
+            };
+            let next_cursor = lists
+                .last()
+                .filter(|_| has_more)
+                .map(|list| list_query.cursor_for(list, CursorDirection::Next));
+            let prev_cursor = lists
+                .first()
+                .filter(|_| has_previous)
+                .map(|list| list_query.cursor_for(list, CursorDirection::Prev));
 
             let response = serde_json::json!({
             "status": "success",
@@ -158,9 +242,10 @@ pub async fn get_users_lists_handler(
             {
                 "lists": lists,
                 "hasMore": has_more,
-                "nextPage": next_page,
-                "prevPage": prev_page,
-                "totalCount": total_count
+                "nextCursor": next_cursor,
+                "prevCursor": prev_cursor,
+                "totalCount": total_count,
+                "searchMode": list_query.search_mode()
             }});
 
             Ok(Json(response))
@@ -173,114 +258,715 @@ pub async fn get_users_lists_handler(
     }
 }
 
+/// Reads one page (plus one extra row, which tells whether another page follows) and the total count if requested.
+async fn fetch_lists(
+    list_query: &ListQuery,
+    user_id: &Uuid,
+    workspace: &CurrentWorkspace,
+    pool: &PgPool,
+) -> Result<(Option<i64>, Vec<ListModel>), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if let Some(threshold) = list_query.fuzzy_threshold() {
+        sqlx::query_scalar!(
+            "SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)",
+            threshold.to_string()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+    }
+
+    let total_count = if list_query.include_total {
+        let mut count_query = QueryBuilder::<Postgres>::new("");
+        list_query.push_count(&mut count_query);
+        list_query.push_filters(&mut count_query, *user_id, workspace);
+
+        Some(
+            count_query
+                .build_query_scalar::<i64>()
+                .fetch_one(&mut *tx)
+                .await?,
+        )
+    } else {
+        None
+    };
+
+    let mut lists_query = QueryBuilder::<Postgres>::new("");
+    list_query.push_select(&mut lists_query);
+    list_query.push_filters(&mut lists_query, *user_id, workspace);
+    list_query.push_keyset(&mut lists_query);
+    list_query.push_order_by(&mut lists_query);
+    lists_query
+        .push(" LIMIT ")
+        .push_bind(list_query.page_size as i64 + 1)
+        .push(" OFFSET ")
+        .push_bind(list_query.offset());
+
+    let lists = lists_query
+        .build_query_as::<ListModel>()
+        .fetch_all(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok((total_count, lists))
+}
+
+/// `PATCH /api/lists/list`, the older form of `PATCH /api/lists/:id` that takes the id from the body.
 pub async fn update_list_handler(
     State(data): State<Arc<AppState>>,
     Extension(current_user): Extension<UserModel>,
+    headers: HeaderMap,
     Json(body): Json<UpdateListSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = sqlx::query!("SELECT * FROM lists WHERE id = $1", body.id)
-        .fetch_one(&data.db)
-        .await;
+    let Some(id) = body.id else {
+        let error_response =
+            json!({"status": "fail", "message": "Provide the id of the item to update"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    };
+
+    update_list(&data, &current_user, id, &headers, body).await
+}
+
+/// Accepts `application/json` and `application/merge-patch+json` bodies alike.
+pub async fn patch_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<UpdateListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    update_list(&data, &current_user, id, &headers, body).await
+}
+
+async fn update_list(
+    data: &AppState,
+    current_user: &UserModel,
+    id: Uuid,
+    headers: &HeaderMap,
+    body: UpdateListSchema,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let required = [
+        ("title", body.title.is_null()),
+        ("importance", body.importance.is_null()),
+        ("status", body.status.is_null()),
+    ];
+    if let Some((field, _)) = required.iter().find(|(_, is_null)| *is_null) {
+        let error_response =
+            json!({"status": "fail", "message": format!("'{}' cannot be null", field)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    validate_list_text(
+        &data.config,
+        body.title.value().map(String::as_str),
+        body.descr.value().map(String::as_str),
+        body.body.value().map(String::as_str),
+    )
+    .map_err(invalid_list)?;
+    let role = require_list_role(&id, &current_user.id, ShareRole::Editor, &data.db).await?;
+
+    let expected_versions = if_match_versions(headers);
+    let query_result = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = $1 AND deleted_at IS NULL"#,
+        id
+    )
+    .fetch_one(&data.db)
+    .await;
 
     match query_result {
-        Ok(row) => {
+        Ok(list) => {
+            let list = ListModel::from(list);
+            if matches!(&expected_versions, Some(versions) if !versions.contains(&list.version)) {
+                return Err(precondition_failed(list, &data.db).await);
+            }
+
+            // Project access carries over to its items, so only owners may move them between projects
+            if body.project_id.clone().apply(list.project_id) != list.project_id
+                && role < ShareRole::Owner
+            {
+                let error_response = json!({"status": "fail", "message": "You need owner access to this item to change its project"});
+                return Err((StatusCode::FORBIDDEN, Json(error_response)));
+            }
+            if let Some(project_id) = body.project_id.value() {
+                require_project_role(project_id, &current_user.id, ShareRole::Editor, &data.db)
+                    .await?;
+            }
+
             let now = Utc::now();
-            let list = ListModel {
-                body: row.body,
-                created_at: row.created_at,
-                descr: row.descr,
-                id: row.id,
-                importance: row.importance.unwrap(),
-                title: row.title,
-                updated_at: row.updated_at,
-                user_id: row.user_id.unwrap(),
+            let status = body.status.apply(Some(list.status)).unwrap_or_default();
+            let completed_at = match (list.status, status) {
+                (ListStatus::Done, ListStatus::Done) => list.completed_at,
+                (_, ListStatus::Done) => Some(now),
+                _ => None,
+            };
+
+            let internal_error = |err: sqlx::Error| {
+                if let Some(message) = list_constraint_message(&err) {
+                    return invalid_list(message);
+                }
+                let error_response = serde_json::json!({"status": "fail", "message": format!("Cannot update this list: {:?}", err)});
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
             };
+            let mut tx = begin_with_actor(&data.db, &current_user.id)
+                .await
+                .map_err(internal_error)?;
 
-            match sqlx::query!("UPDATE lists SET title = $1, descr = $2, importance = $3, updated_at = $4 WHERE id = $5 RETURNING id, title, descr, importance, updated_at, body, created_at, user_id", 
-                body.title.as_deref().unwrap_or(&list.title),
-                body.descr.as_deref().unwrap_or_else(|| list.descr.as_deref().unwrap_or("")),
-                body.importance.unwrap_or(list.importance),
+            match sqlx::query_as!(
+                ListRow,
+                r#"UPDATE lists SET title = $1, descr = $2, body = $3, importance = $4, status = $5, project_id = $6, due_at = $7, completed_at = $8, updated_at = $9
+                WHERE id = $10 AND ($11::int[] IS NULL OR version = ANY($11))
+                RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+                body.title.apply(Some(list.title)),
+                body.descr.apply(list.descr),
+                body.body.apply(list.body),
+                body.importance.apply(Some(list.importance)) as Option<Importance>,
+                status as ListStatus,
+                body.project_id.apply(list.project_id),
+                body.due_at.apply(list.due_at),
+                completed_at,
                 now,
-                list.id
-            ).fetch_one(&data.db).await {   
-              Ok(updated_row) => {
-                    let list_response = ListResponse {
-                        id: updated_row.id,
-                        title: updated_row.title,
-                        descr: updated_row.descr,
-                        importance: updated_row.importance.unwrap(),
-                        updated_at: updated_row.updated_at,
-                        body: updated_row.body,
-                        created_at: updated_row.created_at,
-                        user_id: updated_row.user_id.unwrap(),
-                    };
-
-                    return Ok(Json(json!({"status": "success", "data": {"list": list_response}})));
-                },
-                Err(e) => {
-                     let error_response = serde_json::json!({"status": "fail", "message": format!("Cannot update this list: {:?}", e)});
+                list.id,
+                expected_versions.as_deref()
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            {
+                Ok(Some(updated_list)) => {
+                    tx.commit().await.map_err(internal_error)?;
+                    let mut updated_list = ListModel::from(updated_list);
+                    updated_list.tags = get_list_tags(&updated_list.id, &data.db)
+                        .await
+                        .map_err(internal_error)?;
+                    let etag = list_etag(updated_list.version);
+                    let list_response: ListResponse = updated_list.into();
 
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+                    Ok(([(ETAG, etag)], Json(json!({"status": "success", "data": {"list": list_response}}))))
+                },
+                // Someone else saved between our read and write
+                Ok(None) => {
+                    drop(tx);
+                    Err(stale_version(&list.id, &data.db).await)
+                }
+                Err(e) if is_duplicate_title(&e) => {
+                    let error_response = json!({"status": "fail", "message": "You already have an item with this title"});
+                    Err((StatusCode::CONFLICT, Json(error_response)))
                 }
+                Err(e) => Err(internal_error(e)),
             }
         }
         Err(_) => {
             let error_response =
                 serde_json::json!({"status": "fail", "message": format!("Cannot find this list")});
 
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
         }
     }
 }
 
+/// Moves the item to the trash; it can be restored until the trash is emptied or purged.
 pub async fn delete_list_handler(
     State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let delete_request = sqlx::query!("DELETE FROM lists WHERE id = $1", id)
-        .execute(&data.db)
-        .await;
+    require_list_role(&id, &current_user.id, ShareRole::Owner, &data.db).await?;
+
+    let expected_versions = if_match_versions(&headers);
+    let internal_error = |e: sqlx::Error| {
+        let error_response = serde_json::json!({"status": "fail", "message": format!("Failed to delete this list item: {:?}", e)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+    let mut tx = begin_with_actor(&data.db, &current_user.id)
+        .await
+        .map_err(internal_error)?;
+
+    let delete_request = sqlx::query!(
+        "UPDATE lists SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL AND ($2::int[] IS NULL OR version = ANY($2))",
+        id,
+        expected_versions.as_deref()
+    )
+    .execute(&mut *tx)
+    .await;
 
     match delete_request {
         Ok(result) => {
             if result.rows_affected() == 1 {
-                let success_response = serde_json::json!({"status": "success", "message": "List deleted successfully"});
+                tx.commit().await.map_err(internal_error)?;
+                let success_response =
+                    serde_json::json!({"status": "success", "message": "List moved to the trash"});
                 Ok(Json(success_response))
+            } else if expected_versions.is_some() {
+                drop(tx);
+                Err(stale_version(&id, &data.db).await)
             } else {
                 let error_response = json!({"status": "fail", "message": "List not found"});
                 Err((StatusCode::NOT_FOUND, Json(error_response)))
             }
-        },
-        Err(e) => {
-            let error_response = serde_json::json!({"status": "fail", "message": format!("Failed to delete this list item: {:?}", e)});
+        }
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+pub async fn archive_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    set_archived(&data.db, &current_user.id, &id, true).await
+}
+
+pub async fn unarchive_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    set_archived(&data.db, &current_user.id, &id, false).await
+}
+
+/// Archiving an already archived item keeps its original `archived_at`.
+async fn set_archived(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+    archived: bool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(id, user_id, ShareRole::Editor, pool).await?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot update this list item: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+    let mut tx = begin_with_actor(pool, user_id)
+        .await
+        .map_err(internal_error)?;
+
+    let query_result = sqlx::query_as!(
+        ListRow,
+        r#"UPDATE lists SET archived_at = CASE WHEN $1 THEN COALESCE(archived_at, NOW()) END, updated_at = NOW()
+        WHERE id = $2 AND deleted_at IS NULL
+        RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+        archived,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(Some(list)) => {
+            tx.commit().await.map_err(internal_error)?;
+            let mut list = ListModel::from(list);
+            list.tags = get_list_tags(&list.id, pool)
+                .await
+                .map_err(internal_error)?;
+            Ok(Json(json!({"status": "success", "data": {"list": list}})))
+        }
+        Ok(None) => {
+            let error_response = json!({"status": "fail", "message": "List not found"});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(err) => Err(internal_error(err)),
+    }
+}
+
+pub async fn get_trash_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch the trash: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut lists: Vec<ListModel> = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists
+        WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NOT NULL ORDER BY deleted_at DESC, id"#,
+        current_user.id,
+        workspace.id
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .map(ListModel::from)
+    .collect();
+
+    load_list_tags(&mut lists, &data.db)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "lists": lists,
+            "retentionDays": data.config.trash_retention_days
+        }
+    })))
+}
+
+pub async fn restore_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let internal_error = |err: sqlx::Error| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot restore this list item: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let trashed = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists
+        WHERE id = $1 AND deleted_at IS NOT NULL AND list_role(id, $2) = 'owner'"#,
+        id,
+        current_user.id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(trashed) = trashed else {
+        let error_response = json!({"status": "fail", "message": "List not found in the trash"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    if get_list_by_title(&trashed.title, &trashed.user_id, &data.db)
+        .await
+        .is_some()
+    {
+        let error_response = json!({"status": "fail", "message": "You already have an item with this title, rename it before restoring this one"});
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let mut tx = begin_with_actor(&data.db, &current_user.id)
+        .await
+        .map_err(internal_error)?;
+    let mut list: ListModel = sqlx::query_as!(
+        ListRow,
+        r#"UPDATE lists SET deleted_at = NULL, updated_at = NOW() WHERE id = $1
+        RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+        trashed.id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| {
+        if is_duplicate_title(&err) {
+            let error_response = json!({"status": "fail", "message": "You already have an item with this title, rename it before restoring this one"});
+            return (StatusCode::CONFLICT, Json(error_response));
+        }
+        internal_error(err)
+    })?
+    .into();
+    tx.commit().await.map_err(internal_error)?;
+
+    list.tags = get_list_tags(&list.id, &data.db)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(json!({"status": "success", "data": {"list": list}})))
+}
+
+pub async fn empty_trash_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query!(
+        "DELETE FROM lists WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NOT NULL",
+        current_user.id,
+        workspace.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(result) => Ok(Json(
+            json!({"status": "success", "data": {"deleted": result.rows_affected()}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot empty the trash: {:?}", err)});
             Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
         }
     }
 }
 
+/// 412 carrying the item as it is now, so the client can merge and retry with its ETag.
+async fn precondition_failed(
+    mut list: ListModel,
+    pool: &PgPool,
+) -> (StatusCode, Json<serde_json::Value>) {
+    list.tags = match get_list_tags(&list.id, pool).await {
+        Ok(tags) => tags,
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch tags: {:?}", err)});
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response));
+        }
+    };
+    let error_response = json!({
+        "status": "fail",
+        "message": "This item has been changed since you loaded it",
+        "data": {"list": list, "etag": list_etag(list.version)}
+    });
+    (StatusCode::PRECONDITION_FAILED, Json(error_response))
+}
+
+/// A conditional write matched no row: either the version is stale or the item is gone.
+async fn stale_version(id: &Uuid, pool: &PgPool) -> (StatusCode, Json<serde_json::Value>) {
+    match sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = $1 AND deleted_at IS NULL"#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(list)) => precondition_failed(list.into(), pool).await,
+        _ => {
+            let error_response = json!({"status": "fail", "message": "List not found"});
+            (StatusCode::NOT_FOUND, Json(error_response))
+        }
+    }
+}
+
+/// Gap left between neighbours when positions are (re)assigned.
+const POSITION_STEP: f64 = 1024.0;
+
+/// Below this gap there is no room left for another midpoint and the user's items are renumbered.
+const MIN_POSITION_GAP: f64 = 1e-6;
+
+pub async fn move_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<MoveListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body.after_id.is_none() && body.before_id.is_none() {
+        let error_response =
+            json!({"status": "fail", "message": "Provide after_id, before_id or both"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    if body.after_id == Some(id) || body.before_id == Some(id) {
+        let error_response =
+            json!({"status": "fail", "message": "An item cannot be placed next to itself"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot move this list item: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    // Positions are the creator's own ordering, which people the item is shared with don't change
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+    let is_creator = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM lists WHERE id = $1 AND user_id = $2)",
+        id,
+        current_user.id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?
+    .unwrap_or(false);
+    if !is_creator {
+        let error_response =
+            json!({"status": "fail", "message": "Only the item's creator can reorder it"});
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    // Serialises concurrent moves for the same user so rebalancing never interleaves
+    sqlx::query!(
+        "SELECT id FROM lists WHERE user_id = $1 AND deleted_at IS NULL FOR UPDATE",
+        current_user.id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let mut bounds = neighbour_positions(&mut tx, &current_user.id, &id, &body).await?;
+
+    if let (Some(lower), Some(upper)) = bounds {
+        if upper - lower < MIN_POSITION_GAP {
+            rebalance_positions(&mut tx, &current_user.id)
+                .await
+                .map_err(internal_error)?;
+            bounds = neighbour_positions(&mut tx, &current_user.id, &id, &body).await?;
+        }
+    }
+
+    let position = match bounds {
+        (Some(lower), Some(upper)) => (lower + upper) / 2.0,
+        (Some(lower), None) => lower + POSITION_STEP,
+        (None, Some(upper)) => upper - POSITION_STEP,
+        (None, None) => 0.0,
+    };
+
+    let query_result = sqlx::query_as!(
+        ListRow,
+        r#"UPDATE lists SET position = $1, updated_at = $2 WHERE id = $3 AND user_id = $4 AND deleted_at IS NULL
+        RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+        position,
+        Utc::now(),
+        id,
+        current_user.id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(mut list) = query_result.map(ListModel::from) else {
+        let error_response = json!({"status": "fail", "message": "List not found"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    tx.commit().await.map_err(internal_error)?;
+
+    list.tags = get_list_tags(&list.id, &data.db)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(json!({"status": "success", "data": {"list": list}})))
+}
+
+/// Resolves the positions the moved item has to fit between, filling in the missing side
+/// from the anchor's current neighbour so the item lands directly next to it.
+async fn neighbour_positions(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    id: &Uuid,
+    body: &MoveListSchema,
+) -> Result<(Option<f64>, Option<f64>), (StatusCode, Json<serde_json::Value>)> {
+    let anchor_not_found = || {
+        let error_response =
+            json!({"status": "fail", "message": "Cannot find the item to place this one next to"});
+        (StatusCode::NOT_FOUND, Json(error_response))
+    };
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot move this list item: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut lower = None;
+    let mut upper = None;
+
+    if let Some(after_id) = body.after_id {
+        lower = Some(
+            sqlx::query_scalar!(
+                "SELECT position FROM lists WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+                after_id,
+                user_id
+            )
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(anchor_not_found)?,
+        );
+    }
+
+    if let Some(before_id) = body.before_id {
+        upper = Some(
+            sqlx::query_scalar!(
+                "SELECT position FROM lists WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+                before_id,
+                user_id
+            )
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(anchor_not_found)?,
+        );
+    }
+
+    match (lower, upper) {
+        (Some(lower), Some(upper)) if lower >= upper => {
+            let error_response =
+                json!({"status": "fail", "message": "after_id must come before before_id"});
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+        (Some(lower_position), None) => {
+            upper = sqlx::query_scalar!(
+                "SELECT position FROM lists WHERE user_id = $1 AND deleted_at IS NULL AND position > $2 AND id <> $3 ORDER BY position LIMIT 1",
+                user_id,
+                lower_position,
+                id
+            )
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(internal_error)?;
+        }
+        (None, Some(upper_position)) => {
+            lower = sqlx::query_scalar!(
+                "SELECT position FROM lists WHERE user_id = $1 AND deleted_at IS NULL AND position < $2 AND id <> $3 ORDER BY position DESC LIMIT 1",
+                user_id,
+                upper_position,
+                id
+            )
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(internal_error)?;
+        }
+        _ => {}
+    }
+
+    Ok((lower, upper))
+}
+
+/// Renumbers all of a user's items in their current order with an even gap between them.
+async fn rebalance_positions(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE lists l SET position = ordered.rn * $2::DOUBLE PRECISION
+        FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY position, id) AS rn FROM lists WHERE user_id = $1 AND deleted_at IS NULL) ordered
+        WHERE l.id = ordered.id",
+        user_id,
+        POSITION_STEP
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+fn invalid_list(message: String) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({"status": "fail", "message": message});
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
+}
+
+/// Titles are unique per user among items outside the trash, enforced by `lists_user_id_title_idx`.
+pub fn is_duplicate_title(err: &sqlx::Error) -> bool {
+    err.to_string().contains("lists_user_id_title_idx")
+}
+
 pub async fn get_list_by_title<'a>(
     title: &'a str,
     user_id: &'a Uuid,
     pool: &PgPool,
 ) -> Option<ListModel> {
-    match sqlx::query!(
-        "SELECT * FROM lists WHERE title = $1 AND user_id = $2",
+    sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE title = $1 AND user_id = $2 AND deleted_at IS NULL"#,
         title,
         user_id
     )
     .fetch_one(pool)
     .await
-    {
-        Ok(row) => Some(ListModel {
-            id: row.id,
-            title: row.title,
-            user_id: row.user_id?,
-            descr: row.descr,
-            body: row.body,
-            importance: row.importance?,
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-        }),
-        Err(_) => None,
-    }
+    .ok()
+    .map(ListModel::from)
+}
+
+pub async fn get_list_tags(list_id: &Uuid, pool: &PgPool) -> Result<Vec<TagModel>, sqlx::Error> {
+    sqlx::query_as!(
+        TagModel,
+        "SELECT t.* FROM tags t JOIN list_tags lt ON lt.tag_id = t.id WHERE lt.list_id = $1 ORDER BY t.name",
+        list_id
+    )
+    .fetch_all(pool)
+    .await
 }
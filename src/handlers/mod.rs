@@ -1,13 +1,74 @@
+mod assignee;
+mod attachment;
+mod bulk;
+mod comment;
+mod digest;
+mod event_stream;
 mod health_checker;
+mod history;
 mod list;
+mod notification;
+mod project;
+mod reminder;
+mod share;
+mod sync;
+mod tag;
 mod user_and_auth;
+mod webhook;
+mod workspace;
 
+pub use assignee::{
+    assign_list_handler, get_assignees_handler, load_list_assignees, unassign_list_handler,
+};
+pub use attachment::{
+    delete_attachment_handler, download_attachment_handler, get_attachments_handler,
+    upload_attachments_handler, upload_body_limit,
+};
+pub use bulk::bulk_lists_handler;
+pub use comment::{
+    add_comment_handler, delete_comment_handler, get_comments_handler, update_comment_handler,
+};
+pub use digest::{delete_digest_handler, get_digest_handler, update_digest_handler};
+pub use event_stream::{create_event_ticket_handler, event_stream_handler};
 pub use health_checker::health_checker_handler;
+pub use history::{begin_with_actor, get_list_history_handler, revert_list_handler};
 pub use list::{
-    add_list_handler, delete_list_handler, get_list_by_id_handler, get_users_lists_handler,
-    update_list_handler,
+    add_list_handler, archive_list_handler, delete_list_handler, empty_trash_handler,
+    get_list_by_id_handler, get_list_by_title, get_list_tags, get_trash_handler,
+    get_users_lists_handler, is_duplicate_title, move_list_handler, patch_list_handler,
+    restore_list_handler, unarchive_list_handler, update_list_handler,
+};
+pub use notification::{
+    get_notification_preferences_handler, get_notifications_handler,
+    mark_all_notifications_read_handler, mark_notification_read_handler,
+    mark_notification_unread_handler, notify, update_notification_preferences_handler,
+    NewNotification,
+};
+pub use project::{
+    add_project_handler, delete_project_handler, get_projects_handler, update_project_handler,
+};
+pub use reminder::{add_reminder_handler, delete_reminder_handler, get_reminders_handler};
+pub use share::{
+    get_list_shares_handler, get_project_shares_handler, require_list_role, require_project_role,
+    share_list_handler, share_project_handler, unshare_list_handler, unshare_project_handler,
+};
+pub use sync::{get_sync_handler, push_sync_handler};
+pub use tag::{
+    add_tag_handler, attach_tags_handler, delete_tag_handler, detach_tags_handler,
+    get_tags_handler, load_list_tags, merge_tags_handler, update_tag_handler,
 };
 pub use user_and_auth::{
-    create_user_handler, get_user_by_email, get_user_by_username, login_handler, update_password,
-    upload_img, verify_email,
+    create_user_handler, find_user_by_username, get_user_by_email, get_user_by_username,
+    login_handler, update_password, upload_img, verify_email,
+};
+pub use webhook::{
+    add_webhook_handler, delete_webhook_handler, get_webhook_deliveries_handler,
+    get_webhook_delivery_handler, get_webhook_handler, get_webhooks_handler, ping_webhook_handler,
+    update_webhook_handler,
+};
+pub use workspace::{
+    accept_invitation_handler, add_workspace_handler, delete_workspace_handler,
+    get_invitations_handler, get_members_handler, get_workspace_handler, get_workspaces_handler,
+    invite_member_handler, remove_member_handler, require_workspace_role,
+    revoke_invitation_handler, update_member_handler, update_workspace_handler,
 };
@@ -0,0 +1,255 @@
+use crate::{
+    models::{NotificationModel, NotificationType, UserModel},
+    schemas::{NotificationQuery, UpdateNotificationPreferencesSchema},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Keeps the page's OFFSET within a Postgres BIGINT.
+const MAX_PAGE: usize = i64::MAX as usize / MAX_PAGE_SIZE;
+
+/// An event to put in someone's inbox.
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub kind: NotificationType,
+    pub actor_id: Option<Uuid>,
+    pub list_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub comment_id: Option<Uuid>,
+    pub message: String,
+    pub dedupe_key: Option<String>,
+}
+
+/// Records the notification in the user's inbox. Returns whether it should also go out by email,
+/// which is false when the user turned those emails off or when a notification with the same
+/// `dedupe_key` was already recorded.
+pub async fn notify<'e>(
+    executor: impl PgExecutor<'e>,
+    notification: NewNotification,
+) -> Result<bool, sqlx::Error> {
+    let send_email = sqlx::query_scalar!(
+        r#"WITH inserted AS (
+            INSERT INTO notifications (user_id, type, actor_id, list_id, project_id, comment_id, message, dedupe_key)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT DO NOTHING
+            RETURNING user_id, type
+        )
+        SELECT COALESCE(p.email, TRUE) AS "email!"
+        FROM inserted i LEFT JOIN notification_preferences p ON p.user_id = i.user_id AND p.type = i.type"#,
+        notification.user_id,
+        notification.kind as NotificationType,
+        notification.actor_id,
+        notification.list_id,
+        notification.project_id,
+        notification.comment_id,
+        notification.message,
+        notification.dedupe_key
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(send_email.unwrap_or(false))
+}
+
+pub async fn get_notifications_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Query(params): Query<NotificationQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let page = params.page.unwrap_or(1);
+    let page_size = params.page_size.unwrap_or(20);
+    if !(1..=MAX_PAGE).contains(&page) || !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        let error_response = json!({"status": "fail", "message": format!("'page' must be between 1 and {} and 'page_size' between 1 and {}", MAX_PAGE, MAX_PAGE_SIZE)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    let unread_only = params.unread.unwrap_or(false);
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch notifications: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut notifications = sqlx::query_as!(
+        NotificationModel,
+        r#"SELECT id, user_id, type AS "kind: NotificationType", actor_id, list_id, project_id, comment_id, message, read_at, created_at
+        FROM notifications WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+        ORDER BY created_at DESC, id DESC LIMIT $3 OFFSET $4"#,
+        current_user.id,
+        unread_only,
+        page_size as i64 + 1,
+        ((page - 1) * page_size) as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let unread_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+        current_user.id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let has_more = notifications.len() > page_size;
+    notifications.truncate(page_size);
+    let next_page = if has_more { Some(page + 1) } else { None };
+    let prev_page = if page > 1 { Some(page - 1) } else { None };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "notifications": notifications,
+            "unreadCount": unread_count,
+            "hasMore": has_more,
+            "nextPage": next_page,
+            "prevPage": prev_page
+        }
+    })))
+}
+
+pub async fn mark_notification_read_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    set_read(&data.db, &current_user.id, &id, true).await
+}
+
+pub async fn mark_notification_unread_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    set_read(&data.db, &current_user.id, &id, false).await
+}
+
+pub async fn mark_all_notifications_read_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query!(
+        "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+        current_user.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(result) => Ok(Json(
+            json!({"status": "success", "data": {"updated": result.rows_affected()}}),
+        )),
+        Err(err) => {
+            let error_response = json!({"status": "fail", "message": format!("Cannot update notifications: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Reading something twice keeps the time it was first read.
+async fn set_read(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+    read: bool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        NotificationModel,
+        r#"UPDATE notifications SET read_at = CASE WHEN $1 THEN COALESCE(read_at, NOW()) END
+        WHERE id = $2 AND user_id = $3
+        RETURNING id, user_id, type AS "kind: NotificationType", actor_id, list_id, project_id, comment_id, message, read_at, created_at"#,
+        read,
+        id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(notification)) => Ok(Json(
+            json!({"status": "success", "data": {"notification": notification}}),
+        )),
+        Ok(None) => {
+            let error_response = json!({"status": "fail", "message": "Notification not found"});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(err) => {
+            let error_response = json!({"status": "fail", "message": format!("Cannot update notification: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn get_notification_preferences_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    get_preferences(&data.db, &current_user.id).await
+}
+
+pub async fn update_notification_preferences_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<UpdateNotificationPreferencesSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot update preferences: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    for (kind, email) in body.email {
+        sqlx::query!(
+            "INSERT INTO notification_preferences (user_id, type, email) VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, type) DO UPDATE SET email = EXCLUDED.email",
+            current_user.id,
+            kind as NotificationType,
+            email
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+
+    get_preferences(&data.db, &current_user.id).await
+}
+
+/// Every event type with whether it goes out by email.
+async fn get_preferences(
+    pool: &PgPool,
+    user_id: &Uuid,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let rows = sqlx::query!(
+        r#"SELECT type AS "kind: NotificationType", email FROM notification_preferences WHERE user_id = $1"#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch preferences: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?;
+
+    let mut email: HashMap<NotificationType, bool> = NotificationType::ALL
+        .iter()
+        .map(|kind| (*kind, true))
+        .collect();
+    for row in rows {
+        email.insert(row.kind, row.email);
+    }
+
+    Ok(Json(json!({"status": "success", "data": {"email": email}})))
+}
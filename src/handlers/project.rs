@@ -0,0 +1,155 @@
+use crate::{
+    handlers::{require_project_role, require_workspace_role},
+    models::{CurrentWorkspace, ProjectModel, ShareRole, UserModel, WorkspaceRole},
+    schemas::{CreateProjectSchema, UpdateProjectSchema},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The selected workspace's projects the user can see, their own first.
+pub async fn get_projects_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        ProjectModel,
+        "SELECT * FROM projects
+        WHERE workspace_id = $2 AND project_role(id, $1) IS NOT NULL
+        ORDER BY user_id <> $1, name",
+        current_user.id,
+        workspace.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(projects) => Ok(Json(
+            json!({"status": "success", "data": {"projects": projects}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch projects: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn add_project_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Json(body): Json<CreateProjectSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Member)?;
+    let name = validate_project_name(&body.name)?;
+
+    let query_result = sqlx::query_as!(
+        ProjectModel,
+        "INSERT INTO projects (user_id, name, workspace_id) VALUES ($1, $2, $3) RETURNING *",
+        current_user.id,
+        name,
+        workspace.id
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match query_result {
+        Ok(project) => Ok((
+            StatusCode::CREATED,
+            Json(json!({"status": "success", "data": {"project": project}})),
+        )),
+        Err(err) => Err(project_write_error(err)),
+    }
+}
+
+pub async fn update_project_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateProjectSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_project_role(&id, &current_user.id, ShareRole::Owner, &data.db).await?;
+    let name = validate_project_name(&body.name)?;
+
+    let query_result = sqlx::query_as!(
+        ProjectModel,
+        "UPDATE projects SET name = $1, updated_at = $2 WHERE id = $3 RETURNING *",
+        name,
+        Utc::now(),
+        id
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match query_result {
+        Ok(project) => Ok(Json(
+            json!({"status": "success", "data": {"project": project}}),
+        )),
+        Err(err) => Err(project_write_error(err)),
+    }
+}
+
+/// Deleting a project keeps its items; they simply no longer belong to a project.
+pub async fn delete_project_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_project_role(&id, &current_user.id, ShareRole::Owner, &data.db).await?;
+
+    let delete_request = sqlx::query!("DELETE FROM projects WHERE id = $1", id)
+        .execute(&data.db)
+        .await;
+
+    match delete_request {
+        Ok(result) => {
+            if result.rows_affected() == 1 {
+                let success_response =
+                    json!({"status": "success", "message": "Project deleted successfully"});
+                Ok(Json(success_response))
+            } else {
+                let error_response = json!({"status": "fail", "message": "Project not found"});
+                Err((StatusCode::NOT_FOUND, Json(error_response)))
+            }
+        }
+        Err(e) => {
+            let error_response = json!({"status": "fail", "message": format!("Failed to delete this project: {:?}", e)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+fn validate_project_name(name: &str) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let name = name.trim();
+
+    if name.is_empty() || name.chars().count() > 100 {
+        let error_response =
+            json!({"status": "fail", "message": "Project names must be 1-100 characters"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(name.to_string())
+}
+
+fn project_write_error(err: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    if err
+        .to_string()
+        .contains("duplicate key value violates unique constraint")
+    {
+        let error_response =
+            json!({"status": "fail", "message": "You already have a project with this name"});
+        return (StatusCode::CONFLICT, Json(error_response));
+    }
+
+    let error_response =
+        json!({"status": "fail", "message": format!("Cannot save project: {:?}", err)});
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+}
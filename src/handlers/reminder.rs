@@ -0,0 +1,153 @@
+use crate::{
+    handlers::require_list_role,
+    models::{ReminderModel, ReminderStatus, ShareRole, UserModel},
+    schemas::CreateReminderSchema,
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Most reminders one user can have on a single item.
+const MAX_REMINDERS_PER_ITEM: i64 = 10;
+
+/// Reminders can be at most this far ahead of the due date: one year.
+const MAX_OFFSET_MINUTES: i32 = 365 * 24 * 60;
+
+/// The current user's reminders on an item; everyone sets their own.
+pub async fn get_reminders_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    match sqlx::query_as!(
+        ReminderModel,
+        r#"SELECT id, list_id, user_id, remind_at, offset_minutes, fire_at, status AS "status: ReminderStatus",
+            attempts, retry_at, last_error, sent_at, created_at
+        FROM list_reminders WHERE list_id = $1 AND user_id = $2 ORDER BY fire_at NULLS LAST, created_at"#,
+        id,
+        current_user.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(reminders) => Ok(Json(
+            json!({"status": "success", "data": {"reminders": reminders}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch reminders: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Anyone who can see an item can be reminded of it. Relative reminders follow the due date
+/// when it moves and stay idle while the item has none.
+pub async fn add_reminder_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<CreateReminderSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let invalid = |message: String| {
+        let error_response = json!({"status": "fail", "message": message});
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
+    };
+    match (body.remind_at, body.offset_minutes) {
+        (Some(_), Some(_)) | (None, None) => {
+            return Err(invalid(
+                "Provide either remind_at or offset_minutes".to_string(),
+            ))
+        }
+        (Some(remind_at), None) if remind_at <= Utc::now() => {
+            return Err(invalid("remind_at must be in the future".to_string()))
+        }
+        (None, Some(offset)) if !(0..=MAX_OFFSET_MINUTES).contains(&offset) => {
+            return Err(invalid(format!(
+                "offset_minutes must be between 0 and {}",
+                MAX_OFFSET_MINUTES
+            )))
+        }
+        _ => {}
+    }
+
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot add reminder: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let existing = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM list_reminders WHERE list_id = $1 AND user_id = $2"#,
+        id,
+        current_user.id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+    if existing >= MAX_REMINDERS_PER_ITEM {
+        return Err(invalid(format!(
+            "An item can have at most {} reminders",
+            MAX_REMINDERS_PER_ITEM
+        )));
+    }
+
+    let reminder = sqlx::query_as!(
+        ReminderModel,
+        r#"INSERT INTO list_reminders (list_id, user_id, remind_at, offset_minutes) VALUES ($1, $2, $3, $4)
+        RETURNING id, list_id, user_id, remind_at, offset_minutes, fire_at, status AS "status: ReminderStatus",
+            attempts, retry_at, last_error, sent_at, created_at"#,
+        id,
+        current_user.id,
+        body.remind_at,
+        body.offset_minutes
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"reminder": reminder}})),
+    ))
+}
+
+pub async fn delete_reminder_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, reminder_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query!(
+        "DELETE FROM list_reminders WHERE id = $1 AND list_id = $2 AND user_id = $3",
+        reminder_id,
+        id,
+        current_user.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(result) if result.rows_affected() == 1 => Ok(Json(
+            json!({"status": "success", "message": "Reminder deleted"}),
+        )),
+        Ok(_) => {
+            let error_response = json!({"status": "fail", "message": "Reminder not found"});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot delete reminder: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::{
+    handlers::{notify, NewNotification},
+    models::{NotificationType, ShareModel, ShareRole, UserModel},
+    schemas::CreateShareSchema,
+    utils::send_share_mail,
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// List items and projects are shared the same way, only the tables differ.
+#[derive(Debug, Clone, Copy)]
+enum ShareTarget {
+    List,
+    Project,
+}
+
+impl ShareTarget {
+    fn noun(self) -> &'static str {
+        match self {
+            ShareTarget::List => "item",
+            ShareTarget::Project => "project",
+        }
+    }
+
+    fn not_found(self) -> (StatusCode, Json<serde_json::Value>) {
+        let message = match self {
+            ShareTarget::List => "List not found",
+            ShareTarget::Project => "Project not found",
+        };
+        let error_response = json!({"status": "fail", "message": message});
+        (StatusCode::NOT_FOUND, Json(error_response))
+    }
+}
+
+/// Checks what the user may do with an item that isn't in the trash. Users without any access
+/// get a 404 rather than a 403, so ids of other people's items reveal nothing.
+pub async fn require_list_role(
+    id: &Uuid,
+    user_id: &Uuid,
+    min_role: ShareRole,
+    pool: &PgPool,
+) -> Result<ShareRole, (StatusCode, Json<serde_json::Value>)> {
+    let role = sqlx::query_scalar!(
+        r#"SELECT list_role($1, $2) AS "role: ShareRole" FROM lists WHERE id = $1 AND deleted_at IS NULL"#,
+        id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(role_error)?
+    .flatten();
+
+    check_role(role, min_role, ShareTarget::List)
+}
+
+pub async fn require_project_role(
+    id: &Uuid,
+    user_id: &Uuid,
+    min_role: ShareRole,
+    pool: &PgPool,
+) -> Result<ShareRole, (StatusCode, Json<serde_json::Value>)> {
+    let role = sqlx::query_scalar!(
+        r#"SELECT project_role($1, $2) AS "role: ShareRole""#,
+        id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(role_error)?;
+
+    check_role(role, min_role, ShareTarget::Project)
+}
+
+fn check_role(
+    role: Option<ShareRole>,
+    min_role: ShareRole,
+    target: ShareTarget,
+) -> Result<ShareRole, (StatusCode, Json<serde_json::Value>)> {
+    match role {
+        None => Err(target.not_found()),
+        Some(role) if role < min_role => {
+            let error_response = json!({"status": "fail", "message": format!("You need {} access to this {} to do that", min_role.as_str(), target.noun())});
+            Err((StatusCode::FORBIDDEN, Json(error_response)))
+        }
+        Some(role) => Ok(role),
+    }
+}
+
+fn role_error(err: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response =
+        json!({"status": "fail", "message": format!("Cannot check permissions: {:?}", err)});
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+}
+
+pub async fn get_list_shares_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+    get_shares(ShareTarget::List, &id, &data.db).await
+}
+
+pub async fn share_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<CreateShareSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_list_role(&id, &current_user.id, ShareRole::Owner, &data.db).await?;
+    share(ShareTarget::List, &id, &current_user, body, &data.db).await
+}
+
+/// Owners can take anyone off an item; everyone else can only remove themselves.
+pub async fn unshare_list_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let min_role = if user_id == current_user.id {
+        ShareRole::Viewer
+    } else {
+        ShareRole::Owner
+    };
+    require_list_role(&id, &current_user.id, min_role, &data.db).await?;
+    unshare(ShareTarget::List, &id, &user_id, &data.db).await
+}
+
+pub async fn get_project_shares_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_project_role(&id, &current_user.id, ShareRole::Viewer, &data.db).await?;
+    get_shares(ShareTarget::Project, &id, &data.db).await
+}
+
+/// Sharing a project gives the same role on every item in it.
+pub async fn share_project_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<CreateShareSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_project_role(&id, &current_user.id, ShareRole::Owner, &data.db).await?;
+    share(ShareTarget::Project, &id, &current_user, body, &data.db).await
+}
+
+pub async fn unshare_project_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let min_role = if user_id == current_user.id {
+        ShareRole::Viewer
+    } else {
+        ShareRole::Owner
+    };
+    require_project_role(&id, &current_user.id, min_role, &data.db).await?;
+    unshare(ShareTarget::Project, &id, &user_id, &data.db).await
+}
+
+async fn get_shares(
+    target: ShareTarget,
+    id: &Uuid,
+    pool: &PgPool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = match target {
+        ShareTarget::List => {
+            sqlx::query_as!(
+                ShareModel,
+                r#"SELECT s.user_id, u.username, u.email, s.role AS "role: ShareRole", s.shared_by, s.created_at
+                FROM list_shares s JOIN users u ON u.id = s.user_id WHERE s.list_id = $1 ORDER BY s.created_at"#,
+                id
+            )
+            .fetch_all(pool)
+            .await
+        }
+        ShareTarget::Project => {
+            sqlx::query_as!(
+                ShareModel,
+                r#"SELECT s.user_id, u.username, u.email, s.role AS "role: ShareRole", s.shared_by, s.created_at
+                FROM project_shares s JOIN users u ON u.id = s.user_id WHERE s.project_id = $1 ORDER BY s.created_at"#,
+                id
+            )
+            .fetch_all(pool)
+            .await
+        }
+    };
+
+    match query_result {
+        Ok(shares) => Ok(Json(
+            json!({"status": "success", "data": {"shares": shares}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch shares: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+async fn share(
+    target: ShareTarget,
+    id: &Uuid,
+    current_user: &UserModel,
+    body: CreateShareSchema,
+    pool: &PgPool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let internal_error = |err: sqlx::Error| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot share this {}: {:?}", target.noun(), err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let user = body.user.trim();
+    let recipient = sqlx::query_as!(
+        UserModel,
+        "SELECT * FROM users WHERE username = $1 OR LOWER(email) = LOWER($1)",
+        user
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(internal_error)?;
+    let Some(recipient) = recipient else {
+        let error_response = json!({"status": "fail", "message": "User not found"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let (owner_id, name) = match target {
+        ShareTarget::List => {
+            let list = sqlx::query!("SELECT user_id, title FROM lists WHERE id = $1", id)
+                .fetch_one(pool)
+                .await
+                .map_err(internal_error)?;
+            (list.user_id, list.title)
+        }
+        ShareTarget::Project => {
+            let project = sqlx::query!("SELECT user_id, name FROM projects WHERE id = $1", id)
+                .fetch_one(pool)
+                .await
+                .map_err(internal_error)?;
+            (Some(project.user_id), project.name)
+        }
+    };
+    if recipient.id == current_user.id {
+        let error_response =
+            json!({"status": "fail", "message": "You cannot change your own access"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    if Some(recipient.id) == owner_id {
+        let error_response = json!({"status": "fail", "message": format!("This {} already belongs to {}", target.noun(), recipient.username)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    match target {
+        ShareTarget::List => {
+            sqlx::query!(
+                "INSERT INTO list_shares (list_id, user_id, role, shared_by) VALUES ($1, $2, $3, $4)
+                ON CONFLICT (list_id, user_id) DO UPDATE SET role = EXCLUDED.role, shared_by = EXCLUDED.shared_by",
+                id,
+                recipient.id,
+                body.role as ShareRole,
+                current_user.id
+            )
+            .execute(pool)
+            .await
+        }
+        ShareTarget::Project => {
+            sqlx::query!(
+                "INSERT INTO project_shares (project_id, user_id, role, shared_by) VALUES ($1, $2, $3, $4)
+                ON CONFLICT (project_id, user_id) DO UPDATE SET role = EXCLUDED.role, shared_by = EXCLUDED.shared_by",
+                id,
+                recipient.id,
+                body.role as ShareRole,
+                current_user.id
+            )
+            .execute(pool)
+            .await
+        }
+    }
+    .map_err(internal_error)?;
+
+    let (list_id, project_id) = match target {
+        ShareTarget::List => (Some(*id), None),
+        ShareTarget::Project => (None, Some(*id)),
+    };
+    let notification = NewNotification {
+        user_id: recipient.id,
+        kind: NotificationType::Shared,
+        actor_id: Some(current_user.id),
+        list_id,
+        project_id,
+        comment_id: None,
+        message: format!(
+            "{} shared the {} \"{}\" with you as {}",
+            current_user.username,
+            target.noun(),
+            name,
+            body.role.as_str()
+        ),
+        dedupe_key: None,
+    };
+    if notify(pool, notification).await.map_err(internal_error)? {
+        send_share_mail(
+            &recipient.email,
+            &current_user.username,
+            target.noun(),
+            &name,
+            body.role.as_str(),
+        )
+        .await;
+    }
+
+    get_shares(target, id, pool).await
+}
+
+async fn unshare(
+    target: ShareTarget,
+    id: &Uuid,
+    user_id: &Uuid,
+    pool: &PgPool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = match target {
+        ShareTarget::List => {
+            sqlx::query!(
+                "DELETE FROM list_shares WHERE list_id = $1 AND user_id = $2",
+                id,
+                user_id
+            )
+            .execute(pool)
+            .await
+        }
+        ShareTarget::Project => {
+            sqlx::query!(
+                "DELETE FROM project_shares WHERE project_id = $1 AND user_id = $2",
+                id,
+                user_id
+            )
+            .execute(pool)
+            .await
+        }
+    };
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 1 => Ok(Json(
+            json!({"status": "success", "message": "Access removed"}),
+        )),
+        Ok(_) => {
+            let error_response = json!({"status": "fail", "message": format!("This {} is not shared with that user", target.noun())});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot remove access: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
@@ -0,0 +1,586 @@
+use crate::{
+    config::Config,
+    handlers::{begin_with_actor, is_duplicate_title, load_list_assignees, load_list_tags},
+    models::{
+        CurrentWorkspace, Importance, ListModel, ListRow, ListStatus, ShareRole, UserModel,
+        WorkspaceRole,
+    },
+    schemas::{
+        CreateListSchema, SyncChange, SyncChangeResult, SyncChangeStatus, SyncConflictMode,
+        SyncPushSchema, SyncQuery, UpdateListSchema,
+    },
+    utils::{list_constraint_message, sign_token, validate_list_text, verify_token},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Acquire, PgConnection, Postgres, Transaction};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+const SYNC_TOKEN: &str = "sync";
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 500;
+
+const MAX_SYNC_CHANGES: usize = 500;
+
+/// Where a client is in the change feed: it has every change up to `seq` that was committed in
+/// `snapshot`. Sequence values are taken before commit, so changes still in flight at the time
+/// can commit later with a lower value; those are told apart by their transaction ids.
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncToken {
+    user_id: Uuid,
+    seq: i64,
+    /// `None` before the first sync.
+    snapshot: Option<String>,
+    issued_at: i64,
+    /// Set while a sync is spread over several pages.
+    pass: Option<SyncPass>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncPass {
+    /// Changes up to here have been sent in earlier pages.
+    cursor: i64,
+    /// Taken on the first page; becomes the token's snapshot once the pass is done.
+    snapshot: String,
+    issued_at: i64,
+}
+
+/// Items created, updated and deleted since `since`, oldest change first. Follow `nextToken`
+/// while `hasMore` is set, then keep the last token for the next sync. Created and updated items
+/// are complete and should be upserted; deleted ones are ids that were trashed, deleted for good
+/// or are no longer shared with the user.
+pub async fn get_sync_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Query(params): Query<SyncQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let page_size = params.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        let error_response = json!({"status": "fail", "message": format!("'page_size' must be between 1 and {}", MAX_PAGE_SIZE)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let token = match params.since.as_deref() {
+        Some(since) => verify_token::<SyncToken>(SYNC_TOKEN, since.trim())
+            .filter(|token| token.user_id == current_user.id)
+            .ok_or_else(|| {
+                let error_response = json!({"status": "fail", "message": "Invalid sync token"});
+                (StatusCode::BAD_REQUEST, Json(error_response))
+            })?,
+        None => SyncToken {
+            user_id: current_user.id,
+            seq: 0,
+            snapshot: None,
+            issued_at: Utc::now().timestamp(),
+            pass: None,
+        },
+    };
+    if token.snapshot.is_some() && token_expired(&token, &data.config) {
+        let error_response = json!({"status": "fail", "message": "This sync token has expired, sync again without 'since'"});
+        return Err((StatusCode::GONE, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot sync: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    // One snapshot for the whole page, so what it reports and what the token says agree
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    let pass = match &token.pass {
+        Some(pass) => pass.clone(),
+        None => SyncPass {
+            cursor: 0,
+            snapshot: sqlx::query_scalar!(r#"SELECT pg_current_snapshot()::text AS "snapshot!""#)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(internal_error)?,
+            issued_at: Utc::now().timestamp(),
+        },
+    };
+
+    let mut changes = sqlx::query!(
+        r#"WITH changes AS (
+            SELECT l.id AS list_id, l.change_seq AS seq, FALSE AS access_changed
+            FROM lists l
+            WHERE (l.change_seq > $2 OR (l.change_xid >= pg_snapshot_xmin($3::text::pg_snapshot)
+                    AND NOT pg_visible_in_snapshot(l.change_xid, $3::text::pg_snapshot)))
+                AND (l.workspace_id IN (SELECT workspace_id FROM workspace_members WHERE user_id = $1)
+                    OR l.id IN (SELECT list_id FROM list_shares WHERE user_id = $1)
+                    OR l.project_id IN (SELECT project_id FROM project_shares WHERE user_id = $1))
+                AND list_role(l.id, $1) IS NOT NULL
+            UNION ALL
+            SELECT c.list_id, c.seq, TRUE
+            FROM list_access_changes c
+            WHERE c.user_id = $1 AND (c.seq > $2 OR NOT pg_visible_in_snapshot(c.xid, $3::text::pg_snapshot))
+        )
+        SELECT list_id AS "list_id!", MAX(seq) AS "seq!", bool_or(access_changed) AS "access_changed!"
+        FROM changes
+        GROUP BY list_id
+        HAVING MAX(seq) > $4
+        ORDER BY 2
+        LIMIT $5"#,
+        current_user.id,
+        token.seq,
+        token.snapshot,
+        pass.cursor,
+        page_size as i64 + 1
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let has_more = changes.len() > page_size;
+    changes.truncate(page_size);
+    let list_ids: Vec<Uuid> = changes.iter().map(|change| change.list_id).collect();
+
+    let lists = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = ANY($1) AND list_role(id, $2) IS NOT NULL"#,
+        &list_ids,
+        current_user.id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    let created_ids: HashSet<Uuid> = sqlx::query_scalar!(
+        "SELECT id FROM lists WHERE id = ANY($1) AND ($2::text IS NULL OR NOT pg_visible_in_snapshot(created_xid, $2::text::pg_snapshot))",
+        &list_ids,
+        token.snapshot
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .collect();
+    tx.commit().await.map_err(internal_error)?;
+
+    let (mut created, mut updated, mut deleted) = (Vec::new(), Vec::new(), Vec::new());
+    let mut lists: HashMap<Uuid, ListModel> = lists
+        .into_iter()
+        .map(|list| (list.id, list.into()))
+        .collect();
+    for change in &changes {
+        match lists.remove(&change.list_id) {
+            Some(list) if list.deleted_at.is_some() => deleted.push(list.id),
+            Some(list) if created_ids.contains(&list.id) => created.push(list),
+            Some(list) => updated.push(list),
+            // Gone, or no longer visible to this user
+            None if change.access_changed => deleted.push(change.list_id),
+            None => {}
+        }
+    }
+    // A first sync has nothing to delete
+    if token.snapshot.is_none() {
+        deleted.clear();
+    }
+    for lists in [&mut created, &mut updated] {
+        load_list_tags(lists, &data.db)
+            .await
+            .map_err(internal_error)?;
+        load_list_assignees(lists, &data.db)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    let cursor = changes.last().map_or(pass.cursor, |change| change.seq);
+    let next_token = if has_more {
+        SyncToken {
+            pass: Some(SyncPass { cursor, ..pass }),
+            ..token
+        }
+    } else {
+        SyncToken {
+            user_id: current_user.id,
+            seq: cursor.max(token.seq),
+            snapshot: Some(pass.snapshot),
+            issued_at: pass.issued_at,
+            pass: None,
+        }
+    };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "created": created,
+            "updated": updated,
+            "deleted": deleted,
+            "nextToken": sign_token(SYNC_TOKEN, &next_token),
+            "hasMore": has_more
+        }
+    })))
+}
+
+/// Deletions and lost access are only remembered for `SYNC_TOKEN_TTL_DAYS`.
+fn token_expired(token: &SyncToken, config: &Config) -> bool {
+    token.issued_at < (Utc::now() - Duration::days(config.sync_token_ttl_days)).timestamp()
+}
+
+/// Applies changes made offline, in order, each on its own: one failing or conflicting change
+/// doesn't hold back the others.
+pub async fn push_sync_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Json(body): Json<SyncPushSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body.changes.is_empty() {
+        let error_response = json!({"status": "fail", "message": "Provide at least one change"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    if body.changes.len() > MAX_SYNC_CHANGES {
+        let error_response = json!({"status": "fail", "message": format!("A sync can push at most {} changes", MAX_SYNC_CHANGES)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot apply changes: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let context = PushContext {
+        config: &data.config,
+        user_id: current_user.id,
+        workspace: &workspace,
+        on_conflict: body.on_conflict,
+    };
+    let mut tx = begin_with_actor(&data.db, &current_user.id)
+        .await
+        .map_err(internal_error)?;
+    let mut results = Vec::with_capacity(body.changes.len());
+    for (index, change) in body.changes.into_iter().enumerate() {
+        let id = change.id();
+        let op = change.name();
+        let (status, message, list) = match apply_in_savepoint(&mut tx, &context, change).await {
+            Ok((status, list)) => (status, None, list),
+            Err(message) => (SyncChangeStatus::Failed, Some(message), None),
+        };
+        results.push(SyncChangeResult {
+            index,
+            op,
+            id,
+            status,
+            message,
+            list,
+        });
+    }
+    tx.commit().await.map_err(internal_error)?;
+
+    let (indexes, mut lists): (Vec<usize>, Vec<ListModel>) = results
+        .iter_mut()
+        .filter_map(|result| Some((result.index, result.list.take()?)))
+        .unzip();
+    load_list_tags(&mut lists, &data.db)
+        .await
+        .map_err(internal_error)?;
+    load_list_assignees(&mut lists, &data.db)
+        .await
+        .map_err(internal_error)?;
+    for (index, list) in indexes.into_iter().zip(lists) {
+        results[index].list = Some(list);
+    }
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "applied": count(SyncChangeStatus::Applied),
+            "conflicts": count(SyncChangeStatus::Conflict),
+            "failed": count(SyncChangeStatus::Failed),
+            "results": results
+        }
+    })))
+}
+
+struct PushContext<'a> {
+    config: &'a Config,
+    user_id: Uuid,
+    /// Where new items go, as for `POST /api/lists/list`.
+    workspace: &'a CurrentWorkspace,
+    on_conflict: SyncConflictMode,
+}
+
+type ChangeOutcome = Result<(SyncChangeStatus, Option<ListModel>), String>;
+
+async fn apply_in_savepoint(
+    tx: &mut Transaction<'_, Postgres>,
+    context: &PushContext<'_>,
+    change: SyncChange,
+) -> ChangeOutcome {
+    let database_error = |err: sqlx::Error| format!("Database error: {}", err);
+
+    let mut savepoint = tx.begin().await.map_err(database_error)?;
+    let outcome = match change {
+        SyncChange::Create { id, fields } => create_list(&mut savepoint, context, id, fields).await,
+        SyncChange::Update {
+            id,
+            base_version,
+            fields,
+        } => update_list(&mut savepoint, context, id, base_version, fields).await,
+        SyncChange::Delete { id, base_version } => {
+            delete_list(&mut savepoint, context, id, base_version).await
+        }
+    };
+    match outcome {
+        Ok(outcome) => {
+            savepoint.commit().await.map_err(database_error)?;
+            Ok(outcome)
+        }
+        Err(message) => {
+            savepoint.rollback().await.map_err(database_error)?;
+            Err(message)
+        }
+    }
+}
+
+fn database_error(err: sqlx::Error) -> String {
+    if is_duplicate_title(&err) {
+        return "You already have an item with this title".to_string();
+    }
+    list_constraint_message(&err).unwrap_or_else(|| format!("Database error: {}", err))
+}
+
+async fn create_list(
+    conn: &mut PgConnection,
+    context: &PushContext<'_>,
+    id: Uuid,
+    fields: CreateListSchema,
+) -> ChangeOutcome {
+    // Already pushed once, and the response never made it back
+    let existing = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(database_error)?;
+    if let Some(list) = existing.map(ListModel::from) {
+        if list.user_id != context.user_id {
+            return Err("Another item already has this id".to_string());
+        }
+        let list = list.deleted_at.is_none().then_some(list);
+        return Ok((SyncChangeStatus::Applied, list));
+    }
+
+    if context.workspace.role < WorkspaceRole::Member {
+        return Err(format!(
+            "You need to be a workspace {} to do that",
+            WorkspaceRole::Member.as_str()
+        ));
+    }
+    validate_list_text(
+        context.config,
+        Some(&fields.title),
+        fields.descr.as_deref(),
+        fields.body.as_deref(),
+    )?;
+    if let Some(project_id) = fields.project_id {
+        check_project(conn, context.user_id, project_id).await?;
+    }
+
+    let status = fields.status.unwrap_or_default();
+    let completed_at = (status == ListStatus::Done).then(Utc::now);
+    let list = sqlx::query_as!(
+        ListRow,
+        r#"INSERT INTO lists (id, user_id, title, descr, body, importance, status, project_id, due_at, completed_at, workspace_id, position)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, (SELECT COALESCE(MAX(position), 0) + 1024 FROM lists WHERE user_id = $2))
+        RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+        id,
+        context.user_id,
+        fields.title,
+        fields.descr.unwrap_or_default(),
+        fields.body.unwrap_or_default(),
+        fields.importance as Importance,
+        status as ListStatus,
+        fields.project_id,
+        fields.due_at,
+        completed_at,
+        context.workspace.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(database_error)?;
+
+    Ok((SyncChangeStatus::Applied, Some(list.into())))
+}
+
+async fn update_list(
+    conn: &mut PgConnection,
+    context: &PushContext<'_>,
+    id: Uuid,
+    base_version: Option<i32>,
+    fields: UpdateListSchema,
+) -> ChangeOutcome {
+    let required = [
+        ("title", fields.title.is_null()),
+        ("importance", fields.importance.is_null()),
+        ("status", fields.status.is_null()),
+    ];
+    if let Some((field, _)) = required.iter().find(|(_, is_null)| *is_null) {
+        return Err(format!("'{}' cannot be null", field));
+    }
+    validate_list_text(
+        context.config,
+        fields.title.value().map(String::as_str),
+        fields.descr.value().map(String::as_str),
+        fields.body.value().map(String::as_str),
+    )?;
+
+    let role = list_role(conn, context.user_id, id).await?;
+    if role < ShareRole::Editor {
+        return Err(format!(
+            "You need {} access to this item",
+            ShareRole::Editor.as_str()
+        ));
+    }
+    let list: ListModel = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(database_error)?
+    .ok_or_else(|| "List not found".to_string())?
+    .into();
+
+    if is_conflict(context, base_version, list.version) {
+        return Ok((SyncChangeStatus::Conflict, Some(list)));
+    }
+
+    // Project access carries over to its items, so only owners may move them between projects
+    if fields.project_id.clone().apply(list.project_id) != list.project_id {
+        if role < ShareRole::Owner {
+            return Err("You need owner access to this item to change its project".to_string());
+        }
+        if let Some(project_id) = fields.project_id.value() {
+            check_project(conn, context.user_id, *project_id).await?;
+        }
+    }
+
+    let status = fields.status.apply(Some(list.status)).unwrap_or_default();
+    let completed_at = match (list.status, status) {
+        (ListStatus::Done, ListStatus::Done) => list.completed_at,
+        (_, ListStatus::Done) => Some(Utc::now()),
+        _ => None,
+    };
+    let list = sqlx::query_as!(
+        ListRow,
+        r#"UPDATE lists SET title = $1, descr = $2, body = $3, importance = $4, status = $5, project_id = $6, due_at = $7, completed_at = $8, updated_at = NOW()
+        WHERE id = $9
+        RETURNING id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at"#,
+        fields.title.apply(Some(list.title)),
+        fields.descr.apply(list.descr),
+        fields.body.apply(list.body),
+        fields.importance.apply(Some(list.importance)) as Option<Importance>,
+        status as ListStatus,
+        fields.project_id.apply(list.project_id),
+        fields.due_at.apply(list.due_at),
+        completed_at,
+        id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(database_error)?;
+
+    Ok((SyncChangeStatus::Applied, Some(list.into())))
+}
+
+/// Moves the item to the trash, as `DELETE /api/lists/list/:id` does.
+async fn delete_list(
+    conn: &mut PgConnection,
+    context: &PushContext<'_>,
+    id: Uuid,
+    base_version: Option<i32>,
+) -> ChangeOutcome {
+    let role = list_role(conn, context.user_id, id).await?;
+    if role < ShareRole::Owner {
+        return Err(format!(
+            "You need {} access to this item",
+            ShareRole::Owner.as_str()
+        ));
+    }
+    let list: ListModel = sqlx::query_as!(
+        ListRow,
+        r#"SELECT id, title, user_id AS "user_id!", descr, body, importance AS "importance: Importance", position, version, status AS "status: ListStatus", project_id, workspace_id, due_at, completed_at, created_at, updated_at, archived_at, deleted_at FROM lists WHERE id = $1 FOR UPDATE"#,
+        id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(database_error)?
+    .into();
+
+    // Deleted on both sides
+    if list.deleted_at.is_some() {
+        return Ok((SyncChangeStatus::Applied, None));
+    }
+    if is_conflict(context, base_version, list.version) {
+        return Ok((SyncChangeStatus::Conflict, Some(list)));
+    }
+
+    sqlx::query!("UPDATE lists SET deleted_at = NOW() WHERE id = $1", id)
+        .execute(&mut *conn)
+        .await
+        .map_err(database_error)?;
+
+    Ok((SyncChangeStatus::Applied, None))
+}
+
+fn is_conflict(context: &PushContext<'_>, base_version: Option<i32>, version: i32) -> bool {
+    context.on_conflict == SyncConflictMode::Reject
+        && matches!(base_version, Some(base_version) if base_version != version)
+}
+
+/// Covers items in the trash too, which deletes treat as already done.
+async fn list_role(conn: &mut PgConnection, user_id: Uuid, id: Uuid) -> Result<ShareRole, String> {
+    sqlx::query_scalar!(
+        r#"SELECT list_role($1, $2) AS "role: ShareRole" FROM lists WHERE id = $1"#,
+        id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(database_error)?
+    .flatten()
+    .ok_or_else(|| "List not found".to_string())
+}
+
+async fn check_project(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    project_id: Uuid,
+) -> Result<(), String> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM projects WHERE id = $1 AND project_role(id, $2) >= 'editor')",
+        project_id,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(database_error)?
+    .unwrap_or(false);
+
+    match exists {
+        true => Ok(()),
+        false => Err("Project not found".to_string()),
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{
+    models::{ListModel, TagModel, UserModel},
+    schemas::{CreateTagSchema, MergeTagsSchema, TagAssignmentSchema, UpdateTagSchema},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+const DEFAULT_TAG_COLOR: &str = "#9e9e9e";
+
+pub async fn get_tags_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        TagModel,
+        "SELECT * FROM tags WHERE user_id = $1 ORDER BY name",
+        current_user.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(tags) => Ok(Json(json!({"status": "success", "data": {"tags": tags}}))),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch tags: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn add_tag_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<CreateTagSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let name = validate_tag_name(&body.name)?;
+    let color = match body.color {
+        Some(color) => validate_tag_color(&color)?,
+        None => DEFAULT_TAG_COLOR.to_string(),
+    };
+
+    let query_result = sqlx::query_as!(
+        TagModel,
+        "INSERT INTO tags (user_id, name, color) VALUES ($1, $2, $3) RETURNING *",
+        current_user.id,
+        name,
+        color
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match query_result {
+        Ok(tag) => Ok((
+            StatusCode::CREATED,
+            Json(json!({"status": "success", "data": {"tag": tag}})),
+        )),
+        Err(err) => Err(tag_write_error(err)),
+    }
+}
+
+pub async fn update_tag_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateTagSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let tag = get_user_tag(&id, &current_user.id, &data.db).await?;
+
+    let name = match body.name {
+        Some(name) => validate_tag_name(&name)?,
+        None => tag.name,
+    };
+    let color = match body.color {
+        Some(color) => validate_tag_color(&color)?,
+        None => tag.color,
+    };
+
+    let query_result = sqlx::query_as!(
+        TagModel,
+        "UPDATE tags SET name = $1, color = $2, updated_at = $3 WHERE id = $4 RETURNING *",
+        name,
+        color,
+        Utc::now(),
+        tag.id
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match query_result {
+        Ok(tag) => Ok(Json(json!({"status": "success", "data": {"tag": tag}}))),
+        Err(err) => Err(tag_write_error(err)),
+    }
+}
+
+pub async fn delete_tag_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let delete_request = sqlx::query!(
+        "DELETE FROM tags WHERE id = $1 AND user_id = $2",
+        id,
+        current_user.id
+    )
+    .execute(&data.db)
+    .await;
+
+    match delete_request {
+        Ok(result) => {
+            if result.rows_affected() == 1 {
+                let success_response =
+                    json!({"status": "success", "message": "Tag deleted successfully"});
+                Ok(Json(success_response))
+            } else {
+                let error_response = json!({"status": "fail", "message": "Tag not found"});
+                Err((StatusCode::NOT_FOUND, Json(error_response)))
+            }
+        }
+        Err(e) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Failed to delete this tag: {:?}", e)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Moves every assignment of the source tags onto the target tag and removes the sources.
+pub async fn merge_tags_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<MergeTagsSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let target = get_user_tag(&body.target_id, &current_user.id, &data.db).await?;
+    let mut source_ids: Vec<Uuid> = body
+        .source_ids
+        .into_iter()
+        .filter(|id| *id != target.id)
+        .collect();
+    source_ids.sort();
+    source_ids.dedup();
+
+    if source_ids.is_empty() {
+        let error_response =
+            json!({"status": "fail", "message": "Provide at least one tag to merge"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot merge tags: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let owned_sources = sqlx::query_scalar!(
+        "SELECT id FROM tags WHERE id = ANY($1) AND user_id = $2",
+        &source_ids,
+        current_user.id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    if owned_sources.len() != source_ids.len() {
+        let error_response =
+            json!({"status": "fail", "message": "One or more tags were not found"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    sqlx::query!(
+        "INSERT INTO list_tags (list_id, tag_id) SELECT list_id, $1 FROM list_tags WHERE tag_id = ANY($2) ON CONFLICT DO NOTHING",
+        target.id,
+        &source_ids
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    sqlx::query!("DELETE FROM tags WHERE id = ANY($1)", &source_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(json!({"status": "success", "data": {"tag": target}})))
+}
+
+pub async fn attach_tags_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<TagAssignmentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query!(
+        "INSERT INTO list_tags (list_id, tag_id)
+        SELECT l.id, t.id FROM lists l CROSS JOIN tags t
+        WHERE l.id = ANY($1) AND list_role(l.id, $3) >= 'editor' AND l.deleted_at IS NULL AND t.id = ANY($2) AND t.user_id = $3
+        ON CONFLICT DO NOTHING",
+        &body.list_ids,
+        &body.tag_ids,
+        current_user.id
+    )
+    .execute(&data.db)
+    .await;
+
+    match query_result {
+        Ok(result) => Ok(Json(
+            json!({"status": "success", "data": {"attached": result.rows_affected()}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot attach tags: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn detach_tags_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<TagAssignmentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let query_result = sqlx::query!(
+        "DELETE FROM list_tags lt USING tags t
+        WHERE t.id = lt.tag_id AND t.user_id = $3 AND lt.list_id = ANY($1) AND lt.tag_id = ANY($2)",
+        &body.list_ids,
+        &body.tag_ids,
+        current_user.id
+    )
+    .execute(&data.db)
+    .await;
+
+    match query_result {
+        Ok(result) => Ok(Json(
+            json!({"status": "success", "data": {"detached": result.rows_affected()}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot detach tags: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Fills in `tags` for every list item with a single query, regardless of how many items there are.
+pub async fn load_list_tags(lists: &mut [ListModel], pool: &PgPool) -> Result<(), sqlx::Error> {
+    if lists.is_empty() {
+        return Ok(());
+    }
+
+    let list_ids: Vec<Uuid> = lists.iter().map(|list| list.id).collect();
+
+    let rows = sqlx::query!(
+        "SELECT lt.list_id, t.id, t.user_id, t.name, t.color, t.created_at, t.updated_at
+        FROM list_tags lt JOIN tags t ON t.id = lt.tag_id
+        WHERE lt.list_id = ANY($1) ORDER BY t.name",
+        &list_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tags_by_list: HashMap<Uuid, Vec<TagModel>> = HashMap::new();
+    for row in rows {
+        tags_by_list.entry(row.list_id).or_default().push(TagModel {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            color: row.color,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        });
+    }
+
+    for list in lists.iter_mut() {
+        list.tags = tags_by_list.remove(&list.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+async fn get_user_tag(
+    id: &Uuid,
+    user_id: &Uuid,
+    pool: &PgPool,
+) -> Result<TagModel, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as!(
+        TagModel,
+        "SELECT * FROM tags WHERE id = $1 AND user_id = $2",
+        id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|_| {
+        let error_response = json!({"status": "fail", "message": "Tag not found"});
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+fn validate_tag_name(name: &str) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let name = name.trim();
+
+    if name.is_empty() || name.chars().count() > 50 || name.contains(',') {
+        let error_response = json!({"status": "fail", "message": "Tag names must be 1-50 characters and cannot contain commas"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(name.to_string())
+}
+
+fn validate_tag_color(color: &str) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let is_hex = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+    if !is_hex {
+        let error_response =
+            json!({"status": "fail", "message": "Tag color must be a hex value like #ff9800"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(color.to_lowercase())
+}
+
+fn tag_write_error(err: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    if err
+        .to_string()
+        .contains("duplicate key value violates unique constraint")
+    {
+        let error_response =
+            json!({"status": "fail", "message": "You already have a tag with this name"});
+        return (StatusCode::CONFLICT, Json(error_response));
+    }
+
+    let error_response =
+        json!({"status": "fail", "message": format!("Cannot save tag: {:?}", err)});
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+}
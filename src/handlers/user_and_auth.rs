@@ -47,10 +47,10 @@ pub async fn create_user_handler(
                 Ok(_) => {
                     send_otp_mail(&body.email, &otp_code, &body.username).await;
 
-                     return Ok((StatusCode::CREATED, Json(user_response)));
+                     Ok((StatusCode::CREATED, Json(user_response)))
                 },
                 Err(e)=>{
-                return Err(e);
+                Err(e)
                 }
             }
         }
@@ -64,17 +64,17 @@ pub async fn create_user_handler(
                 return Err((StatusCode::BAD_REQUEST, Json(error_response)));
             }
 
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"status": "fail", "message": format!("{:?}", e)}))));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"status": "fail", "message": format!("{:?}", e)}))))
         }
     }
 }
 
 pub async fn get_user_by_email(email: &str, pool: &PgPool) -> Option<UserModel> {
-     match sqlx::query_as!(UserModel, "SELECT * FROM users WHERE email = $1", email.to_string()).fetch_one(pool).await   
+     sqlx::query_as!(UserModel, "SELECT * FROM users WHERE email = $1", email.to_string()).fetch_one(pool).await.ok()
+}
 
-    {
-        Ok(user) => Some(user), Err(_) => None,
-    }
+pub async fn find_user_by_username(username: &str, pool: &PgPool) -> Result<Option<UserModel>, sqlx::Error> {
+     sqlx::query_as!(UserModel, "SELECT * FROM users WHERE username = $1", username).fetch_optional(pool).await
 }
 
 pub async fn login_handler( State(data): State<Arc<AppState>>,
@@ -135,7 +135,7 @@ pub async fn verify_email(State(data): State<Arc<AppState>>,
 
     match otp_doc{
         Some(otp_doc) => {
-            if otp_doc.otp.len() == 0 || otp_doc.email != body.email.to_string(){
+            if otp_doc.otp.is_empty() || otp_doc.email != body.email{
               return  Err((StatusCode::BAD_REQUEST, Json(error_response)));                
             }
 
@@ -165,7 +165,7 @@ pub async fn verify_email(State(data): State<Arc<AppState>>,
                             Ok(Json(response))
                                 }
                                 Err(err) => {
-                                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"status": "fail", "message": format!("{:?}", err)})),))
+                                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"status": "fail", "message": format!("{:?}", err)})),))
                                 }
                             }
                             
@@ -179,7 +179,7 @@ pub async fn verify_email(State(data): State<Arc<AppState>>,
                 Err(_err) => Err((StatusCode::BAD_REQUEST, Json(error_response)))
             }
            }else {
-              return  Err((StatusCode::BAD_REQUEST, Json(error_response)));                
+              Err((StatusCode::BAD_REQUEST, Json(error_response)))
            }
         }
         None => {
@@ -189,15 +189,11 @@ pub async fn verify_email(State(data): State<Arc<AppState>>,
 }
 
 pub async fn otp_fetch_service( pool: &PgPool, otp: &str) ->Option<OtpModel> {
-    match sqlx::query_as!(
+    sqlx::query_as!(
         OtpModel,
         "SELECT * FROM otps WHERE otp = $1", 
         otp.to_string(),
-    ).fetch_one(pool).await
-
-    {
-        Ok(otp_docs) => Some(otp_docs),Err(_) => None,
-    }
+    ).fetch_one(pool).await.ok()
 }
 
 pub async fn otp_creator_service( State(data): State<Arc<AppState>>, Json(otp_body): Json<OtpSchema>)  -> Result<String, (StatusCode, Json<serde_json::Value>)> {
@@ -269,7 +265,7 @@ pub async fn upload_img(State(data): State<Arc<AppState>>, Extension(current_use
                     }
                 }
             }
-            Err(e) => {
+            Err(_e) => {
                     let error_response = serde_json::json!({"status": "fail", "message": "Cannot upload image"});
                     return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
             }
@@ -314,41 +310,37 @@ pub async fn update_password(State(data): State<Arc<AppState>>, Extension(curren
         Ok(Json(response))
         }
         Err(err) => {
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"status": "fail", "message": format!("{:?}", err)})),))
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"status": "fail", "message": format!("{:?}", err)})),))
         }
     }
 }
 
 pub async fn get_user_by_username(State(data): State<Arc<AppState>>, Path(username): Path<String>)-> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result =  sqlx::query_as!(UserModel, "SELECT * FROM users WHERE username = $1", username).fetch_one(&data.db).await;
+    let query_result = find_user_by_username(&username, &data.db).await;
 
     match query_result{
-        Ok(user) => {
+        Ok(Some(user)) => {
                 let user_response: UserResponse = user.into();
                 let response = serde_json::json!({
                   "status": "success",
                   "data": user_response
             	});
             	
-            return Ok(Json(response));
+            Ok(Json(response))
         }
-        Err(err) =>{
-
-            let error_response: Value;
-
-            if err.to_string() == "no rows returned by a query that expected to return at least one row" {
-                let message = "User not found";
-                error_response = json!({
-                    "status": "fail",
-                    "message":  message.to_string(),
-                });   
-            }else{
-                 error_response = json!({
-                    "status": "fail",
-                    "message":  format!("{:?}", err),
-                });   
-            }
+        Ok(None) =>{
+            let error_response = json!({
+                "status": "fail",
+                "message": "User not found",
+            });
             Err((StatusCode::NOT_FOUND, Json(error_response)))
         }
+        Err(err) =>{
+            let error_response: Value = json!({
+                "status": "fail",
+                "message":  format!("{:?}", err),
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,368 @@
+use crate::{
+    models::{
+        UserModel, WebhookDeliveryAttemptModel, WebhookDeliveryModel, WebhookDeliveryStatus,
+        WebhookModel, WEBHOOK_EVENTS,
+    },
+    schemas::{CreateWebhookSchema, UpdateWebhookSchema, WebhookDeliveryQuery},
+    utils::{check_webhook_url, generate_webhook_secret},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const MAX_WEBHOOKS_PER_USER: i64 = 20;
+
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Keeps the page's OFFSET within a Postgres BIGINT.
+const MAX_PAGE: usize = i64::MAX as usize / MAX_PAGE_SIZE;
+
+pub async fn get_webhooks_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        WebhookModel,
+        "SELECT id, user_id, url, events, active, consecutive_failures, disabled_at, created_at, updated_at
+        FROM webhooks WHERE user_id = $1 ORDER BY created_at",
+        current_user.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(webhooks) => Ok(Json(
+            json!({"status": "success", "data": {"webhooks": webhooks}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch webhooks: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn get_webhook_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let webhook = find_webhook(&id, &current_user.id, &data.db).await?;
+
+    Ok(Json(
+        json!({"status": "success", "data": {"webhook": webhook}}),
+    ))
+}
+
+/// Webhooks receive the events they subscribe to for every item their owner can see. The
+/// signing secret is only returned here, when the webhook is created.
+pub async fn add_webhook_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<CreateWebhookSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let url = check_webhook_url(body.url.trim(), data.config.webhook_allow_private_urls)
+        .await
+        .map_err(invalid)?;
+    let events = validate_events(body.events)?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot add webhook: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let existing = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM webhooks WHERE user_id = $1"#,
+        current_user.id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+    if existing >= MAX_WEBHOOKS_PER_USER {
+        return Err(invalid(format!(
+            "You can have at most {} webhooks",
+            MAX_WEBHOOKS_PER_USER
+        )));
+    }
+
+    let secret = generate_webhook_secret();
+    let webhook = sqlx::query_as!(
+        WebhookModel,
+        "INSERT INTO webhooks (user_id, url, secret, events) VALUES ($1, $2, $3, $4)
+        RETURNING id, user_id, url, events, active, consecutive_failures, disabled_at, created_at, updated_at",
+        current_user.id,
+        url.as_str(),
+        secret,
+        &events
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"webhook": webhook, "secret": secret}})),
+    ))
+}
+
+pub async fn update_webhook_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateWebhookSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let webhook = find_webhook(&id, &current_user.id, &data.db).await?;
+
+    let url = match body.url {
+        Some(url) => check_webhook_url(url.trim(), data.config.webhook_allow_private_urls)
+            .await
+            .map_err(invalid)?
+            .to_string(),
+        None => webhook.url,
+    };
+    let events = match body.events {
+        Some(events) => validate_events(events)?,
+        None => webhook.events,
+    };
+
+    match sqlx::query_as!(
+        WebhookModel,
+        "UPDATE webhooks SET url = $1, events = $2, active = COALESCE($3, active),
+            consecutive_failures = CASE WHEN $3 THEN 0 ELSE consecutive_failures END,
+            disabled_at = CASE WHEN $3 THEN NULL WHEN NOT $3 THEN COALESCE(disabled_at, NOW()) ELSE disabled_at END,
+            updated_at = NOW()
+        WHERE id = $4
+        RETURNING id, user_id, url, events, active, consecutive_failures, disabled_at, created_at, updated_at",
+        url,
+        &events,
+        body.active,
+        id
+    )
+    .fetch_one(&data.db)
+    .await
+    {
+        Ok(webhook) => Ok(Json(
+            json!({"status": "success", "data": {"webhook": webhook}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot update webhook: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Also drops its queued deliveries and delivery log.
+pub async fn delete_webhook_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query!(
+        "DELETE FROM webhooks WHERE id = $1 AND user_id = $2",
+        id,
+        current_user.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(result) if result.rows_affected() == 1 => Ok(Json(
+            json!({"status": "success", "message": "Webhook deleted"}),
+        )),
+        Ok(_) => Err(not_found()),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot delete webhook: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Queues a `ping` event, to check that the receiver gets and verifies deliveries. Works on
+/// disabled webhooks too.
+pub async fn ping_webhook_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let webhook = find_webhook(&id, &current_user.id, &data.db).await?;
+
+    match sqlx::query_as!(
+        WebhookDeliveryModel,
+        r#"INSERT INTO webhook_deliveries (webhook_id, event, payload)
+        VALUES ($1, 'ping', jsonb_build_object('event', 'ping', 'createdAt', NOW(), 'actorId', $2::uuid,
+            'data', jsonb_build_object('webhook_id', $1::uuid)))
+        RETURNING id, webhook_id, event, payload, status AS "status: WebhookDeliveryStatus", attempts,
+            next_attempt_at, last_status_code, last_error, delivered_at, created_at"#,
+        webhook.id,
+        current_user.id
+    )
+    .fetch_one(&data.db)
+    .await
+    {
+        Ok(delivery) => Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({"status": "success", "data": {"delivery": delivery}})),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot ping webhook: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// The delivery log, newest first.
+pub async fn get_webhook_deliveries_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<WebhookDeliveryQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let page = params.page.unwrap_or(1);
+    let page_size = params.page_size.unwrap_or(20);
+    if !(1..=MAX_PAGE).contains(&page) || !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        let error_response = json!({"status": "fail", "message": format!("'page' must be between 1 and {} and 'page_size' between 1 and {}", MAX_PAGE, MAX_PAGE_SIZE)});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+    find_webhook(&id, &current_user.id, &data.db).await?;
+
+    let mut deliveries = sqlx::query_as!(
+        WebhookDeliveryModel,
+        r#"SELECT id, webhook_id, event, payload, status AS "status: WebhookDeliveryStatus", attempts,
+            next_attempt_at, last_status_code, last_error, delivered_at, created_at
+        FROM webhook_deliveries WHERE webhook_id = $1
+        ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3"#,
+        id,
+        page_size as i64 + 1,
+        ((page - 1) * page_size) as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch deliveries: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?;
+
+    let has_more = deliveries.len() > page_size;
+    deliveries.truncate(page_size);
+    let next_page = if has_more { Some(page + 1) } else { None };
+    let prev_page = if page > 1 { Some(page - 1) } else { None };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "deliveries": deliveries,
+            "hasMore": has_more,
+            "nextPage": next_page,
+            "prevPage": prev_page
+        }
+    })))
+}
+
+/// One delivery with every attempt made at it.
+pub async fn get_webhook_delivery_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_webhook(&id, &current_user.id, &data.db).await?;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch delivery: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let delivery = sqlx::query_as!(
+        WebhookDeliveryModel,
+        r#"SELECT id, webhook_id, event, payload, status AS "status: WebhookDeliveryStatus", attempts,
+            next_attempt_at, last_status_code, last_error, delivered_at, created_at
+        FROM webhook_deliveries WHERE id = $1 AND webhook_id = $2"#,
+        delivery_id,
+        id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        let error_response = json!({"status": "fail", "message": "Delivery not found"});
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })?;
+
+    let attempts = sqlx::query_as!(
+        WebhookDeliveryAttemptModel,
+        "SELECT * FROM webhook_delivery_attempts WHERE delivery_id = $1 ORDER BY created_at",
+        delivery_id
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "data": {"delivery": delivery, "attempts": attempts}}),
+    ))
+}
+
+async fn find_webhook(
+    id: &Uuid,
+    user_id: &Uuid,
+    pool: &PgPool,
+) -> Result<WebhookModel, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as!(
+        WebhookModel,
+        "SELECT id, user_id, url, events, active, consecutive_failures, disabled_at, created_at, updated_at
+        FROM webhooks WHERE id = $1 AND user_id = $2",
+        id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch webhook: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?
+    .ok_or_else(not_found)
+}
+
+fn validate_events(
+    mut events: Vec<String>,
+) -> Result<Vec<String>, (StatusCode, Json<serde_json::Value>)> {
+    events.sort();
+    events.dedup();
+    if events.is_empty() {
+        return Err(invalid("Subscribe to at least one event".to_string()));
+    }
+    let unknown: Vec<&str> = events
+        .iter()
+        .map(String::as_str)
+        .filter(|event| !WEBHOOK_EVENTS.contains(event))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(invalid(format!(
+            "Unknown event(s): {}. Available: {}",
+            unknown.join(", "),
+            WEBHOOK_EVENTS.join(", ")
+        )));
+    }
+    Ok(events)
+}
+
+fn invalid(message: String) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({"status": "fail", "message": message});
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(error_response))
+}
+
+fn not_found() -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({"status": "fail", "message": "Webhook not found"});
+    (StatusCode::NOT_FOUND, Json(error_response))
+}
@@ -0,0 +1,581 @@
+use crate::{
+    models::{
+        CurrentWorkspace, UserModel, WorkspaceInvitationModel, WorkspaceMemberModel,
+        WorkspaceModel, WorkspaceRole,
+    },
+    schemas::{
+        AcceptInvitationSchema, CreateInvitationSchema, CreateWorkspaceSchema, UpdateMemberSchema,
+        UpdateWorkspaceSchema,
+    },
+    utils::{send_invitation_mail, sign_token, verify_token},
+    AppState,
+};
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const INVITATION_TOKEN: &str = "invitation";
+
+/// Fails with a 403 unless the user has at least `min_role` in the selected workspace.
+pub fn require_workspace_role(
+    workspace: &CurrentWorkspace,
+    min_role: WorkspaceRole,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if workspace.role >= min_role {
+        return Ok(());
+    }
+
+    let error_response = json!({"status": "fail", "message": format!("You need to be a workspace {} to do that", min_role.as_str())});
+    Err((StatusCode::FORBIDDEN, Json(error_response)))
+}
+
+/// The user's workspaces, personal one first, with their role in each.
+pub async fn get_workspaces_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        WorkspaceModel,
+        r#"SELECT w.*, m.role AS "role?: WorkspaceRole"
+        FROM workspaces w JOIN workspace_members m ON m.workspace_id = w.id
+        WHERE m.user_id = $1 ORDER BY w.personal DESC, w.name"#,
+        current_user.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(workspaces) => Ok(Json(
+            json!({"status": "success", "data": {"workspaces": workspaces}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch workspaces: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn add_workspace_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<CreateWorkspaceSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let name = validate_workspace_name(&body.name)?;
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot create workspace: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let mut workspace = sqlx::query_as!(
+        WorkspaceModel,
+        r#"INSERT INTO workspaces (name, created_by) VALUES ($1, $2)
+        RETURNING *, NULL::workspace_role AS "role?: WorkspaceRole""#,
+        name,
+        current_user.id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    sqlx::query!(
+        "INSERT INTO workspace_members (workspace_id, user_id, role) VALUES ($1, $2, 'owner')",
+        workspace.id,
+        current_user.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    workspace.role = Some(WorkspaceRole::Owner);
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"workspace": workspace}})),
+    ))
+}
+
+pub async fn get_workspace_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let workspace = get_workspace(&workspace, &data.db).await?;
+    Ok(Json(
+        json!({"status": "success", "data": {"workspace": workspace}}),
+    ))
+}
+
+pub async fn update_workspace_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Json(body): Json<UpdateWorkspaceSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Admin)?;
+    let name = validate_workspace_name(&body.name)?;
+
+    sqlx::query!(
+        "UPDATE workspaces SET name = $1, updated_at = NOW() WHERE id = $2",
+        name,
+        workspace.id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot update workspace: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?;
+
+    let workspace = get_workspace(&workspace, &data.db).await?;
+    Ok(Json(
+        json!({"status": "success", "data": {"workspace": workspace}}),
+    ))
+}
+
+/// Deletes the workspace along with every project and item in it.
+pub async fn delete_workspace_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Owner)?;
+    ensure_team_workspace(&workspace)?;
+
+    match sqlx::query!("DELETE FROM workspaces WHERE id = $1", workspace.id)
+        .execute(&data.db)
+        .await
+    {
+        Ok(_) => Ok(Json(
+            json!({"status": "success", "message": "Workspace deleted"}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot delete workspace: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn get_members_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match sqlx::query_as!(
+        WorkspaceMemberModel,
+        r#"SELECT m.user_id, u.username, u.email, m.role AS "role: WorkspaceRole", m.created_at
+        FROM workspace_members m JOIN users u ON u.id = m.user_id
+        WHERE m.workspace_id = $1 ORDER BY m.role DESC, u.username"#,
+        workspace.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(members) => Ok(Json(
+            json!({"status": "success", "data": {"members": members}}),
+        )),
+        Err(err) => {
+            let error_response =
+                json!({"status": "fail", "message": format!("Cannot fetch members: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+pub async fn update_member_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Path((_, user_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateMemberSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Admin)?;
+    ensure_team_workspace(&workspace)?;
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot update member: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let current_role = lock_member(&mut tx, &workspace.id, &user_id)
+        .await
+        .map_err(internal_error)?;
+    let Some(current_role) = current_role else {
+        return Err(member_not_found());
+    };
+    ensure_can_manage(&workspace, current_role)?;
+    ensure_can_manage(&workspace, body.role)?;
+
+    sqlx::query!(
+        "UPDATE workspace_members SET role = $1 WHERE workspace_id = $2 AND user_id = $3",
+        body.role as WorkspaceRole,
+        workspace.id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    ensure_owner_left(&mut tx, &workspace.id)
+        .await
+        .map_err(internal_error)??;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "data": {"userId": user_id, "role": body.role}}),
+    ))
+}
+
+/// Admins remove members below them; anyone can leave on their own.
+pub async fn remove_member_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Path((_, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_team_workspace(&workspace)?;
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot remove member: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let current_role = lock_member(&mut tx, &workspace.id, &user_id)
+        .await
+        .map_err(internal_error)?;
+    let Some(current_role) = current_role else {
+        return Err(member_not_found());
+    };
+    if user_id != current_user.id {
+        require_workspace_role(&workspace, WorkspaceRole::Admin)?;
+        ensure_can_manage(&workspace, current_role)?;
+    }
+
+    sqlx::query!(
+        "DELETE FROM workspace_members WHERE workspace_id = $1 AND user_id = $2",
+        workspace.id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    ensure_owner_left(&mut tx, &workspace.id)
+        .await
+        .map_err(internal_error)??;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "message": "Member removed"}),
+    ))
+}
+
+/// Invitations that haven't been accepted yet, including expired ones.
+pub async fn get_invitations_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Admin)?;
+
+    match sqlx::query_as!(
+        WorkspaceInvitationModel,
+        r#"SELECT id, workspace_id, email, role AS "role: WorkspaceRole", invited_by, expires_at, accepted_at, created_at
+        FROM workspace_invitations WHERE workspace_id = $1 AND accepted_at IS NULL ORDER BY created_at DESC"#,
+        workspace.id
+    )
+    .fetch_all(&data.db)
+    .await
+    {
+        Ok(invitations) => Ok(Json(
+            json!({"status": "success", "data": {"invitations": invitations}}),
+        )),
+        Err(err) => {
+            let error_response = json!({"status": "fail", "message": format!("Cannot fetch invitations: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Emails a signed accept link. The link only works for an account with the invited address.
+pub async fn invite_member_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Json(body): Json<CreateInvitationSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Admin)?;
+    ensure_team_workspace(&workspace)?;
+    ensure_can_manage(&workspace, body.role)?;
+
+    let email = body.email.trim().to_lowercase();
+    if email.len() > 255 || !email.contains('@') {
+        let error_response = json!({"status": "fail", "message": "Provide a valid email address"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot send invitation: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let already_member = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM workspace_members m JOIN users u ON u.id = m.user_id
+        WHERE m.workspace_id = $1 AND LOWER(u.email) = $2)",
+        workspace.id,
+        email
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?
+    .unwrap_or(false);
+    if already_member {
+        let error_response =
+            json!({"status": "fail", "message": "This person is already a member"});
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    // A new id on every invite makes earlier links for the same address stop working
+    let invitation = sqlx::query_as!(
+        WorkspaceInvitationModel,
+        r#"INSERT INTO workspace_invitations (workspace_id, email, role, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (workspace_id, LOWER(email)) WHERE accepted_at IS NULL
+        DO UPDATE SET id = uuid_generate_v4(), role = EXCLUDED.role, invited_by = EXCLUDED.invited_by,
+            expires_at = EXCLUDED.expires_at, created_at = NOW()
+        RETURNING id, workspace_id, email, role AS "role: WorkspaceRole", invited_by, expires_at, accepted_at, created_at"#,
+        workspace.id,
+        email,
+        body.role as WorkspaceRole,
+        current_user.id,
+        Utc::now() + Duration::days(data.config.invitation_ttl_days)
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let workspace = get_workspace(&workspace, &data.db).await?;
+    let link = format!(
+        "{}?token={}",
+        data.config.invitation_accept_url,
+        sign_token(INVITATION_TOKEN, &invitation.id)
+    );
+    send_invitation_mail(&email, &current_user.username, &workspace.name, &link).await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "data": {"invitation": invitation}})),
+    ))
+}
+
+pub async fn revoke_invitation_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(workspace): Extension<CurrentWorkspace>,
+    Path((_, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    require_workspace_role(&workspace, WorkspaceRole::Admin)?;
+
+    match sqlx::query!(
+        "DELETE FROM workspace_invitations WHERE id = $1 AND workspace_id = $2 AND accepted_at IS NULL",
+        invitation_id,
+        workspace.id
+    )
+    .execute(&data.db)
+    .await
+    {
+        Ok(result) if result.rows_affected() == 1 => Ok(Json(
+            json!({"status": "success", "message": "Invitation revoked"}),
+        )),
+        Ok(_) => {
+            let error_response = json!({"status": "fail", "message": "Invitation not found"});
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(err) => {
+            let error_response = json!({"status": "fail", "message": format!("Cannot revoke invitation: {:?}", err)});
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Joins the workspace with the invited role; someone who already is a member keeps the higher
+/// of the two roles.
+pub async fn accept_invitation_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(current_user): Extension<UserModel>,
+    Json(body): Json<AcceptInvitationSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let Some(invitation_id) = verify_token::<Uuid>(INVITATION_TOKEN, body.token.trim()) else {
+        let error_response = json!({"status": "fail", "message": "Invalid invitation link"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    };
+    let internal_error = |err: sqlx::Error| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot accept invitation: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let invitation = sqlx::query_as!(
+        WorkspaceInvitationModel,
+        r#"SELECT id, workspace_id, email, role AS "role: WorkspaceRole", invited_by, expires_at, accepted_at, created_at
+        FROM workspace_invitations WHERE id = $1 AND accepted_at IS NULL FOR UPDATE"#,
+        invitation_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(invitation) = invitation else {
+        let error_response =
+            json!({"status": "fail", "message": "This invitation is no longer valid"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+    if invitation.expires_at < Utc::now() {
+        let error_response =
+            json!({"status": "fail", "message": "This invitation has expired, ask for a new one"});
+        return Err((StatusCode::GONE, Json(error_response)));
+    }
+    if !invitation.email.eq_ignore_ascii_case(&current_user.email) {
+        let error_response = json!({"status": "fail", "message": "This invitation was sent to another email address"});
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    sqlx::query!(
+        "INSERT INTO workspace_members (workspace_id, user_id, role) VALUES ($1, $2, $3)
+        ON CONFLICT (workspace_id, user_id) DO UPDATE SET role = GREATEST(workspace_members.role, EXCLUDED.role)",
+        invitation.workspace_id,
+        current_user.id,
+        invitation.role as WorkspaceRole
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    sqlx::query!(
+        "UPDATE workspace_invitations SET accepted_at = NOW() WHERE id = $1",
+        invitation.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    let workspace = sqlx::query_as!(
+        WorkspaceModel,
+        r#"SELECT w.*, m.role AS "role?: WorkspaceRole"
+        FROM workspaces w JOIN workspace_members m ON m.workspace_id = w.id AND m.user_id = $2
+        WHERE w.id = $1"#,
+        invitation.workspace_id,
+        current_user.id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(
+        json!({"status": "success", "data": {"workspace": workspace}}),
+    ))
+}
+
+async fn get_workspace(
+    workspace: &CurrentWorkspace,
+    pool: &PgPool,
+) -> Result<WorkspaceModel, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as!(
+        WorkspaceModel,
+        r#"SELECT *, $2::workspace_role AS "role?: WorkspaceRole" FROM workspaces WHERE id = $1"#,
+        workspace.id,
+        workspace.role as WorkspaceRole
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot fetch workspace: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })
+}
+
+/// Personal workspaces only ever have their owner in them.
+fn ensure_team_workspace(
+    workspace: &CurrentWorkspace,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if !workspace.personal {
+        return Ok(());
+    }
+
+    let error_response =
+        json!({"status": "fail", "message": "This can't be done in a personal workspace"});
+    Err((StatusCode::BAD_REQUEST, Json(error_response)))
+}
+
+/// Owners manage everyone; admins only manage, and hand out, roles below their own.
+fn ensure_can_manage(
+    workspace: &CurrentWorkspace,
+    role: WorkspaceRole,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if workspace.role == WorkspaceRole::Owner || role < workspace.role {
+        return Ok(());
+    }
+
+    let error_response = json!({"status": "fail", "message": format!("Only workspace owners can manage {}s", role.as_str())});
+    Err((StatusCode::FORBIDDEN, Json(error_response)))
+}
+
+/// Locks the workspace's member list for the rest of the transaction and returns the member's role.
+async fn lock_member(
+    tx: &mut Transaction<'_, Postgres>,
+    workspace_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<Option<WorkspaceRole>, sqlx::Error> {
+    let members = sqlx::query!(
+        r#"SELECT user_id, role AS "role: WorkspaceRole" FROM workspace_members WHERE workspace_id = $1 FOR UPDATE"#,
+        workspace_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(members
+        .into_iter()
+        .find(|member| member.user_id == *user_id)
+        .map(|member| member.role))
+}
+
+async fn ensure_owner_left(
+    tx: &mut Transaction<'_, Postgres>,
+    workspace_id: &Uuid,
+) -> Result<Result<(), (StatusCode, Json<serde_json::Value>)>, sqlx::Error> {
+    let has_owner = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM workspace_members WHERE workspace_id = $1 AND role = 'owner')",
+        workspace_id
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .unwrap_or(false);
+
+    if has_owner {
+        Ok(Ok(()))
+    } else {
+        let error_response = json!({"status": "fail", "message": "A workspace needs at least one owner, make someone else owner first"});
+        Ok(Err((StatusCode::CONFLICT, Json(error_response))))
+    }
+}
+
+fn member_not_found() -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({"status": "fail", "message": "Member not found"});
+    (StatusCode::NOT_FOUND, Json(error_response))
+}
+
+fn validate_workspace_name(name: &str) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let name = name.trim();
+
+    if name.is_empty() || name.chars().count() > 100 {
+        let error_response =
+            json!({"status": "fail", "message": "Workspace names must be 1-100 characters"});
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(name.to_string())
+}
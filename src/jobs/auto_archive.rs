@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::config::Config;
+
+const ARCHIVE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Archives items that were completed, and left untouched, more than `auto_archive_after_days` ago.
+/// Unarchiving bumps `updated_at`, so an item brought back by hand is not archived again straight away.
+pub fn spawn_auto_archive(pool: PgPool, config: Config) {
+    if config.auto_archive_after_days <= 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ARCHIVE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let archive_result = sqlx::query!(
+                "UPDATE lists SET archived_at = NOW()
+                WHERE status = 'done' AND archived_at IS NULL AND deleted_at IS NULL
+                AND completed_at < NOW() - make_interval(days => $1)
+                AND updated_at < NOW() - make_interval(days => $1)",
+                config.auto_archive_after_days as i32
+            )
+            .execute(&pool)
+            .await;
+
+            match archive_result {
+                Ok(result) if result.rows_affected() > 0 => {
+                    println!(
+                        "Auto-archived {} completed list item(s)",
+                        result.rows_affected()
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("Failed to auto-archive completed items: {:?}", err),
+            }
+        }
+    });
+}
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::{
+    models::DigestFrequency,
+    utils::{send_digest_mail, DigestEmail, DigestItem, DigestSection},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Digests claimed per transaction. A full batch is followed by another one straight away.
+const BATCH_SIZE: i64 = 20;
+
+/// A digest that fails to send is tried again this much later, unless the next regular one is
+/// due first.
+const RETRY_DELAY_MINUTES: i32 = 10;
+
+/// Emails digests as they come due. Every server runs this worker; rows are claimed with
+/// `FOR UPDATE SKIP LOCKED`, so each digest is sent by exactly one of them.
+pub fn spawn_digest_delivery(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            loop {
+                match deliver_digests(&pool).await {
+                    Ok(claimed) if claimed as i64 == BATCH_SIZE => continue,
+                    Ok(_) => break,
+                    Err(err) => {
+                        eprintln!("Failed to deliver digests: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Sends one batch of due digests and schedules the next ones. Returns how many were claimed.
+async fn deliver_digests(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let digests = sqlx::query!(
+        r#"SELECT d.user_id, d.frequency AS "frequency: DigestFrequency", d.timezone, u.username, u.email,
+            (date_trunc('day', NOW() AT TIME ZONE d.timezone) + INTERVAL '1 day') AT TIME ZONE d.timezone AS "today_ends_at!",
+            (date_trunc('day', NOW() AT TIME ZONE d.timezone) + INTERVAL '8 days') AT TIME ZONE d.timezone AS "week_ends_at!",
+            COALESCE(d.last_sent_at, NOW() - CASE WHEN d.frequency = 'weekly' THEN INTERVAL '7 days' ELSE INTERVAL '1 day' END) AS "completed_since!"
+        FROM email_digests d JOIN users u ON u.id = d.user_id
+        WHERE d.next_send_at <= NOW()
+        ORDER BY d.next_send_at
+        LIMIT $1
+        FOR UPDATE OF d SKIP LOCKED"#,
+        BATCH_SIZE
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for digest in &digests {
+        // Items the user is responsible for: assigned to them, or created by them and assigned
+        // to nobody
+        let items = sqlx::query!(
+            r#"SELECT l.title, l.status = 'done' AS "done!", l.due_at,
+                to_char(COALESCE(CASE WHEN l.status = 'done' THEN l.completed_at END, l.due_at) AT TIME ZONE $2,
+                    'Dy DD Mon HH24:MI') AS "when!"
+            FROM lists l
+            WHERE (EXISTS (SELECT 1 FROM list_assignees a WHERE a.list_id = l.id AND a.user_id = $1)
+                OR (l.user_id = $1 AND NOT EXISTS (SELECT 1 FROM list_assignees a WHERE a.list_id = l.id)))
+            AND l.deleted_at IS NULL AND l.archived_at IS NULL
+            AND ((l.status <> 'done' AND l.due_at < $3) OR (l.status = 'done' AND l.completed_at >= $4))
+            AND list_role(l.id, $1) IS NOT NULL
+            ORDER BY l.due_at, l.completed_at DESC"#,
+            digest.user_id,
+            digest.timezone,
+            digest.week_ends_at,
+            digest.completed_since
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut overdue = DigestSection::new("Overdue");
+        let mut due_today = DigestSection::new("Due today");
+        let mut due_this_week = DigestSection::new("Due this week");
+        let mut completed = DigestSection::new("Recently completed");
+        let now = chrono::Utc::now();
+        for item in items {
+            let section = match item.due_at {
+                _ if item.done => &mut completed,
+                Some(due_at) if due_at < now => &mut overdue,
+                Some(due_at) if due_at < digest.today_ends_at => &mut due_today,
+                _ => &mut due_this_week,
+            };
+            section.push(DigestItem {
+                title: item.title,
+                when: item.when,
+            });
+        }
+        let sections: Vec<DigestSection> = [overdue, due_today, due_this_week, completed]
+            .into_iter()
+            .filter(|section| section.total > 0)
+            .collect();
+
+        // Nothing to report is not worth an email
+        let send_result = if sections.is_empty() {
+            Ok(())
+        } else {
+            let email = digest.email.clone();
+            let content = DigestEmail {
+                username: digest.username.clone(),
+                period: digest.frequency.as_str().to_string(),
+                sections,
+            };
+            // Run on its own task so a panicking mailer only fails this digest
+            tokio::spawn(async move {
+                send_digest_mail(&email, &content)
+                    .await
+                    .map_err(|err| err.to_string())
+            })
+            .await
+            .unwrap_or_else(|err| Err(err.to_string()))
+        };
+
+        match send_result {
+            Ok(()) => {
+                sqlx::query!(
+                    "UPDATE email_digests SET last_sent_at = NOW(),
+                        next_send_at = next_digest_at(frequency, send_time, weekday, timezone, NOW())
+                    WHERE user_id = $1",
+                    digest.user_id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            Err(message) => {
+                eprintln!("Failed to send digest to {}: {}", digest.email, message);
+                sqlx::query!(
+                    "UPDATE email_digests SET next_send_at = LEAST(
+                        NOW() + make_interval(mins => $1),
+                        next_digest_at(frequency, send_time, weekday, timezone, NOW()))
+                    WHERE user_id = $2",
+                    RETRY_DELAY_MINUTES,
+                    digest.user_id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(digests.len())
+}
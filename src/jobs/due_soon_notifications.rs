@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::{
+    config::Config,
+    handlers::{notify, NewNotification},
+    models::NotificationType,
+    utils::send_due_soon_mail,
+};
+
+const DUE_SOON_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Notifies the assignees of open items that come due within `due_soon_hours`, or their creator
+/// when nobody is assigned. Each due date is only reported once per person, even with several
+/// servers running, and moving the due date reports it again.
+pub fn spawn_due_soon_notifications(pool: PgPool, config: Config) {
+    if config.due_soon_hours <= 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DUE_SOON_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = notify_due_soon(&pool, config.due_soon_hours).await {
+                eprintln!("Failed to send due soon notifications: {:?}", err);
+            }
+        }
+    });
+}
+
+async fn notify_due_soon(pool: &PgPool, due_soon_hours: i64) -> Result<(), sqlx::Error> {
+    let due_items = sqlx::query!(
+        r#"WITH due AS (
+            SELECT l.id, l.title, l.due_at, r.user_id,
+                'due_soon:' || l.id || ':' || EXTRACT(EPOCH FROM l.due_at)::BIGINT AS dedupe_key
+            FROM lists l
+            CROSS JOIN LATERAL (
+                SELECT a.user_id FROM list_assignees a WHERE a.list_id = l.id
+                UNION
+                SELECT l.user_id WHERE NOT EXISTS (SELECT 1 FROM list_assignees a WHERE a.list_id = l.id)
+            ) r
+            WHERE l.due_at > NOW() AND l.due_at <= NOW() + make_interval(hours => $1)
+            AND l.status <> 'done' AND l.deleted_at IS NULL AND l.archived_at IS NULL
+        )
+        SELECT d.id AS "list_id!", d.title AS "title!", d.due_at AS "due_at!", d.dedupe_key AS "dedupe_key!",
+            u.id AS user_id, u.email
+        FROM due d JOIN users u ON u.id = d.user_id
+        WHERE list_role(d.id, u.id) IS NOT NULL
+        AND NOT EXISTS (SELECT 1 FROM notifications n WHERE n.user_id = u.id AND n.dedupe_key = d.dedupe_key)"#,
+        due_soon_hours as i32
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut notified = 0;
+    for item in due_items {
+        let due_at = item.due_at.format("%Y-%m-%d %H:%M UTC").to_string();
+        let notification = NewNotification {
+            user_id: item.user_id,
+            kind: NotificationType::DueSoon,
+            actor_id: None,
+            list_id: Some(item.list_id),
+            project_id: None,
+            comment_id: None,
+            message: format!("\"{}\" is due {}", item.title, due_at),
+            dedupe_key: Some(item.dedupe_key),
+        };
+        if notify(pool, notification).await? {
+            send_due_soon_mail(&item.email, &item.title, &due_at).await;
+        }
+        notified += 1;
+    }
+
+    if notified > 0 {
+        println!("Sent {} due soon notification(s)", notified);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Drops stored idempotent responses once they are past their 24 hour replay window.
+pub fn spawn_idempotency_cleanup(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = sqlx::query!(
+                "DELETE FROM idempotency_keys WHERE created_at < NOW() - INTERVAL '24 hours'"
+            )
+            .execute(&pool)
+            .await
+            {
+                eprintln!("Failed to clean up idempotency keys: {:?}", err);
+            }
+        }
+    });
+}
@@ -0,0 +1,91 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::models::{ListChangeModel, ListChangeNotice, StreamUsers};
+
+/// Channel the `lists_notify_change` and `list_comments_notify_change` triggers notify on.
+const LIST_CHANGES_CHANNEL: &str = "list_changes";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Listens for item changes committed by any server and hands them to this server's open event
+/// streams, along with which of their users can see the item. Notifications sent while the
+/// connection is down are lost, so streams are told to resync whenever it drops.
+pub fn spawn_list_change_listener(
+    pool: PgPool,
+    changes: broadcast::Sender<ListChangeNotice>,
+    stream_users: StreamUsers,
+) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = listen(&pool, &changes, &stream_users).await {
+                eprintln!("List change listener failed: {:?}", err);
+            }
+            // Sending only fails when no stream is open, which is fine
+            let _ = changes.send(ListChangeNotice::Resync);
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+async fn listen(
+    pool: &PgPool,
+    changes: &broadcast::Sender<ListChangeNotice>,
+    stream_users: &StreamUsers,
+) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(LIST_CHANGES_CHANNEL).await?;
+
+    loop {
+        // `None` means the connection dropped; the next call reconnects and listens again
+        let Some(notification) = listener.try_recv().await? else {
+            let _ = changes.send(ListChangeNotice::Resync);
+            continue;
+        };
+        let change = match serde_json::from_str::<ListChangeModel>(notification.payload()) {
+            Ok(change) => change,
+            Err(err) => {
+                eprintln!(
+                    "Ignoring malformed list change {:?}: {:?}",
+                    notification.payload(),
+                    err
+                );
+                continue;
+            }
+        };
+
+        let users = stream_users.snapshot();
+        if users.is_empty() {
+            continue;
+        }
+        match viewers(pool, &change.list_id, &users).await {
+            Ok(viewers) => {
+                let _ = changes.send(ListChangeNotice::Change(change, Arc::new(viewers)));
+            }
+            Err(err) => {
+                eprintln!("Cannot check access to list {}: {:?}", change.list_id, err);
+                let _ = changes.send(ListChangeNotice::Resync);
+            }
+        }
+    }
+}
+
+/// Which of `user_ids` can see the item.
+async fn viewers(
+    pool: &PgPool,
+    list_id: &Uuid,
+    user_ids: &[Uuid],
+) -> Result<HashSet<Uuid>, sqlx::Error> {
+    let viewers = sqlx::query_scalar!(
+        r#"SELECT u AS "user_id!" FROM UNNEST($2::uuid[]) AS u WHERE list_role($1, u) IS NOT NULL"#,
+        list_id,
+        user_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(viewers.into_iter().collect())
+}
@@ -0,0 +1,21 @@
+mod auto_archive;
+mod digest_delivery;
+mod due_soon_notifications;
+mod idempotency_cleanup;
+mod list_change_listener;
+mod orphaned_files_cleanup;
+mod reminder_delivery;
+mod sync_cleanup;
+mod trash_purge;
+mod webhook_delivery;
+
+pub use auto_archive::spawn_auto_archive;
+pub use digest_delivery::spawn_digest_delivery;
+pub use due_soon_notifications::spawn_due_soon_notifications;
+pub use idempotency_cleanup::spawn_idempotency_cleanup;
+pub use list_change_listener::spawn_list_change_listener;
+pub use orphaned_files_cleanup::spawn_orphaned_files_cleanup;
+pub use reminder_delivery::spawn_reminder_delivery;
+pub use sync_cleanup::spawn_sync_cleanup;
+pub use trash_purge::spawn_trash_purge;
+pub use webhook_delivery::spawn_webhook_delivery;
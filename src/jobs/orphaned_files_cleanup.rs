@@ -0,0 +1,50 @@
+use std::{sync::Arc, time::Duration};
+
+use sqlx::PgPool;
+
+use crate::storage::Storage;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const BATCH_SIZE: i64 = 100;
+
+/// Removes files whose attachment is gone from storage, e.g. after their list item was purged
+/// from the trash. Files that can't be deleted stay queued for the next run.
+pub fn spawn_orphaned_files_cleanup(pool: PgPool, storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let storage_keys = match sqlx::query_scalar!(
+                "SELECT storage_key FROM orphaned_files ORDER BY created_at LIMIT $1",
+                BATCH_SIZE
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(storage_keys) => storage_keys,
+                Err(err) => {
+                    eprintln!("Failed to load orphaned files: {:?}", err);
+                    continue;
+                }
+            };
+
+            for storage_key in storage_keys {
+                if let Err(err) = storage.delete(&storage_key).await {
+                    eprintln!("Failed to delete orphaned file {}: {}", storage_key, err);
+                    continue;
+                }
+                if let Err(err) = sqlx::query!(
+                    "DELETE FROM orphaned_files WHERE storage_key = $1",
+                    storage_key
+                )
+                .execute(&pool)
+                .await
+                {
+                    eprintln!("Failed to dequeue orphaned file {}: {:?}", storage_key, err);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::runtime::Handle;
+
+use crate::utils::send_reminder_mail;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Reminders claimed at a time. A full batch is followed by another one straight away.
+const BATCH_SIZE: i64 = 20;
+
+/// How long a worker has to send the reminders it claimed. Ones it hasn't recorded by then, e.g.
+/// because the server stopped, are picked up again by the next worker.
+const CLAIM_SECONDS: f64 = 15.0 * 60.0;
+
+/// Failed sends are retried with exponential backoff, starting at a minute, until this many
+/// attempts have been made.
+const MAX_ATTEMPTS: i32 = 5;
+
+/// Emails reminders as they come up. Every server runs this worker; rows are claimed with
+/// `FOR UPDATE SKIP LOCKED` and held until the claim runs out, so each reminder is sent by
+/// exactly one of them.
+pub fn spawn_reminder_delivery(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            loop {
+                match deliver_reminders(&pool).await {
+                    Ok(claimed) if claimed as i64 == BATCH_SIZE => continue,
+                    Ok(_) => break,
+                    Err(err) => {
+                        eprintln!("Failed to deliver reminders: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Claims one batch of due reminders, sends them and records how each went. Returns how many
+/// were claimed.
+async fn deliver_reminders(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    // Claimed in a statement of its own, so no rows stay locked while emails go out
+    let reminders = sqlx::query!(
+        r#"WITH due AS (
+            SELECT id FROM list_reminders
+            WHERE status = 'pending' AND COALESCE(retry_at, fire_at) <= NOW()
+                AND (locked_until IS NULL OR locked_until < NOW())
+            ORDER BY COALESCE(retry_at, fire_at)
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE list_reminders r SET locked_until = NOW() + make_interval(secs => $2)
+        FROM due, lists l, users u
+        WHERE r.id = due.id AND l.id = r.list_id AND u.id = r.user_id
+        RETURNING r.id, r.attempts, r.locked_until AS "locked_until!", u.email, l.title, l.due_at,
+            (l.deleted_at IS NULL AND l.status <> 'done' AND list_role(l.id, r.user_id) IS NOT NULL) AS "deliverable!""#,
+        BATCH_SIZE,
+        CLAIM_SECONDS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for reminder in &reminders {
+        // Results only count while the claim is still ours; rescheduling the reminder drops it
+        if !reminder.deliverable {
+            sqlx::query!(
+                "UPDATE list_reminders SET status = 'skipped', locked_until = NULL WHERE id = $1 AND locked_until = $2",
+                reminder.id,
+                reminder.locked_until
+            )
+            .execute(pool)
+            .await?;
+            continue;
+        }
+
+        let email = reminder.email.clone();
+        let title = reminder.title.clone();
+        let due_at = reminder
+            .due_at
+            .map(|due_at| due_at.format("%Y-%m-%d %H:%M UTC").to_string());
+        // lettre's SMTP transport blocks, so the send runs on the blocking pool instead of a
+        // runtime worker; a panicking mailer only fails this reminder
+        let runtime = Handle::current();
+        let send_result = tokio::task::spawn_blocking(move || {
+            runtime
+                .block_on(send_reminder_mail(&email, &title, due_at.as_deref()))
+                .map_err(|err| err.to_string())
+        })
+        .await
+        .unwrap_or_else(|err| Err(err.to_string()));
+
+        let attempts = reminder.attempts + 1;
+        match send_result {
+            Ok(()) => {
+                sqlx::query!(
+                    "UPDATE list_reminders SET status = 'sent', attempts = $1, sent_at = NOW(), retry_at = NULL, last_error = NULL, locked_until = NULL
+                    WHERE id = $2 AND locked_until = $3",
+                    attempts,
+                    reminder.id,
+                    reminder.locked_until
+                )
+                .execute(pool)
+                .await?;
+            }
+            Err(message) => {
+                eprintln!("Failed to send reminder {}: {}", reminder.id, message);
+                sqlx::query!(
+                    "UPDATE list_reminders SET attempts = $1::INT, last_error = $2, locked_until = NULL,
+                        status = CASE WHEN $1 >= $3 THEN 'failed' ELSE 'pending' END::reminder_status,
+                        retry_at = CASE WHEN $1 >= $3 THEN NULL ELSE NOW() + make_interval(mins => POWER(2, $1::INT - 1)::INT) END
+                    WHERE id = $4 AND locked_until = $5",
+                    attempts,
+                    message,
+                    MAX_ATTEMPTS,
+                    reminder.id,
+                    reminder.locked_until
+                )
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+
+    Ok(reminders.len())
+}
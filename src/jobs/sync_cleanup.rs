@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::config::Config;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Forgets deletions and access changes once no valid sync token can still ask for them. Kept a
+/// day past the token lifetime, as a change can be recorded a little before the snapshot that
+/// first misses it.
+pub fn spawn_sync_cleanup(pool: PgPool, config: Config) {
+    let retention_days = config.sync_token_ttl_days + 1;
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = sqlx::query!(
+                "DELETE FROM list_access_changes WHERE created_at < NOW() - make_interval(days => $1)",
+                retention_days as i32
+            )
+            .execute(&pool)
+            .await
+            {
+                eprintln!("Failed to clean up list access changes: {:?}", err);
+            }
+        }
+    });
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::config::Config;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Permanently deletes list items that have been in the trash longer than the retention period.
+pub fn spawn_trash_purge(pool: PgPool, config: Config) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let purge_result = sqlx::query!(
+                "DELETE FROM lists WHERE deleted_at < NOW() - make_interval(days => $1)",
+                config.trash_retention_days as i32
+            )
+            .execute(&pool)
+            .await;
+
+            match purge_result {
+                Ok(result) if result.rows_affected() > 0 => {
+                    println!(
+                        "Purged {} list item(s) from the trash",
+                        result.rows_affected()
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("Failed to purge the trash: {:?}", err),
+            }
+        }
+    });
+}
@@ -0,0 +1,550 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use sqlx::PgPool;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    utils::{resolve_webhook_url, sign_webhook},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Deliveries claimed at once, sent concurrently. A full batch is followed by another one
+/// straight away.
+const BATCH_SIZE: i64 = 20;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a claimed delivery is left to the worker that claimed it. Well beyond a send's
+/// lookup and request timeouts; if the worker dies, another one sends it again after this.
+const CLAIM_SECONDS: f64 = 2.0 * 60.0;
+
+/// Failed deliveries are retried with exponential backoff, starting at a minute, until this
+/// many attempts have been made (a little over four hours in all).
+const MAX_ATTEMPTS: i32 = 9;
+
+/// A webhook is disabled once this many deliveries in a row have run out of attempts.
+const DISABLE_AFTER_FAILURES: i32 = 5;
+
+/// Most of a receiver's response body kept in the delivery log on failure.
+const MAX_LOGGED_RESPONSE: usize = 1024;
+
+/// Sends queued webhook deliveries. Every server runs this worker; rows are claimed with
+/// `FOR UPDATE SKIP LOCKED` and pushed back by `CLAIM_SECONDS`, so each delivery is attempted
+/// by exactly one of them at a time.
+pub fn spawn_webhook_delivery(pool: PgPool, config: Config) {
+    let cleanup_pool = pool.clone();
+    let retention_days = config.webhook_log_retention_days;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = sqlx::query!(
+                "DELETE FROM webhook_deliveries WHERE status <> 'pending' AND created_at < NOW() - make_interval(days => $1)",
+                retention_days as i32
+            )
+            .execute(&cleanup_pool)
+            .await
+            {
+                eprintln!("Failed to clean up webhook deliveries: {:?}", err);
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            loop {
+                match deliver_webhooks(&pool, config.webhook_allow_private_urls).await {
+                    Ok(claimed) if claimed as i64 == BATCH_SIZE => continue,
+                    Ok(_) => break,
+                    Err(err) => {
+                        eprintln!("Failed to deliver webhooks: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Client for one delivery. With `pinned` addresses the host is never looked up again, so it
+/// can't be rebound to a private address between the URL check and the request.
+fn webhook_client(host: &str, pinned: &[SocketAddr]) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        // A redirect could point anywhere, including addresses the URL check keeps out
+        .redirect(reqwest::redirect::Policy::none());
+    if !pinned.is_empty() {
+        builder = builder.resolve_to_addrs(host, pinned);
+    }
+    builder.build()
+}
+
+struct Attempt {
+    delivery_id: Uuid,
+    webhook_id: Uuid,
+    /// `next_attempt_at` as claimed; the result is only recorded while the claim still holds.
+    claimed_until: chrono::DateTime<chrono::Utc>,
+    event: String,
+    attempts: i32,
+    status_code: Option<i32>,
+    error: Option<String>,
+    duration_ms: i32,
+}
+
+/// Sends one batch of due deliveries and records how each went. Returns how many were claimed.
+/// No transaction is held while sending: the batch is claimed in one statement, and each
+/// result is recorded on its own once it is in.
+async fn deliver_webhooks(pool: &PgPool, allow_private: bool) -> Result<usize, sqlx::Error> {
+    // Deliveries for disabled webhooks wait until they are turned back on, except pings
+    let deliveries = sqlx::query!(
+        r#"WITH due AS (
+            SELECT d.id
+            FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id
+            WHERE d.status = 'pending' AND d.next_attempt_at <= NOW() AND (w.active OR d.event = 'ping')
+            ORDER BY d.next_attempt_at
+            LIMIT $1
+            FOR UPDATE OF d SKIP LOCKED
+        )
+        UPDATE webhook_deliveries d SET next_attempt_at = NOW() + make_interval(secs => $2)
+        FROM due, webhooks w
+        WHERE d.id = due.id AND w.id = d.webhook_id
+        RETURNING d.id, d.webhook_id, d.event, d.payload, d.attempts, d.next_attempt_at AS claimed_until,
+            w.url, w.secret"#,
+        BATCH_SIZE,
+        CLAIM_SECONDS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut sends = JoinSet::new();
+    for delivery in &deliveries {
+        let delivery_id = delivery.id;
+        let webhook_id = delivery.webhook_id;
+        let claimed_until = delivery.claimed_until;
+        let attempts = delivery.attempts + 1;
+        let url = delivery.url.clone();
+        let secret = delivery.secret.clone();
+        let event = delivery.event.clone();
+        let body = serde_json::to_vec(&delivery.payload).expect("JSON payload serializes");
+
+        sends.spawn(async move {
+            let started = Instant::now();
+            let checked =
+                resolve_webhook_url(&url, allow_private)
+                    .await
+                    .and_then(|(url, addrs)| {
+                        let client = webhook_client(url.host_str().unwrap_or_default(), &addrs)
+                            .map_err(|err| err.to_string())?;
+                        Ok((url, client))
+                    });
+            let (status_code, error) = match checked {
+                Ok((url, client)) => {
+                    let timestamp = chrono::Utc::now().timestamp();
+                    let signature = sign_webhook(&secret, timestamp, &body);
+                    let response = client
+                        .post(url)
+                        .header("Content-Type", "application/json")
+                        .header("X-Webhook-Id", delivery_id.to_string())
+                        .header("X-Webhook-Event", &event)
+                        .header("X-Webhook-Timestamp", timestamp.to_string())
+                        .header("X-Webhook-Signature", format!("sha256={}", signature))
+                        .body(body)
+                        .send()
+                        .await;
+                    match response {
+                        Ok(response) if response.status().is_success() => {
+                            (Some(response.status().as_u16() as i32), None)
+                        }
+                        Ok(response) => {
+                            let status = response.status();
+                            let text = response.text().await.unwrap_or_default();
+                            let text: String = text.chars().take(MAX_LOGGED_RESPONSE).collect();
+                            (
+                                Some(status.as_u16() as i32),
+                                Some(format!("Receiver responded with {}: {}", status, text)),
+                            )
+                        }
+                        Err(err) => (None, Some(err.to_string())),
+                    }
+                }
+                Err(message) => (None, Some(message)),
+            };
+
+            Attempt {
+                delivery_id,
+                webhook_id,
+                claimed_until,
+                event,
+                attempts,
+                status_code,
+                error,
+                duration_ms: started.elapsed().as_millis() as i32,
+            }
+        });
+    }
+
+    while let Some(result) = sends.join_next().await {
+        let attempt = match result {
+            Ok(attempt) => attempt,
+            Err(err) => {
+                // The delivery stays pending and is picked up again on a later run
+                eprintln!("Webhook delivery task failed: {:?}", err);
+                continue;
+            }
+        };
+        // One failure to record doesn't hold up the rest; its claim runs out and it is sent again
+        if let Err(err) = record_attempt(pool, &attempt).await {
+            eprintln!(
+                "Failed to record webhook delivery {}: {:?}",
+                attempt.delivery_id, err
+            );
+        }
+    }
+
+    Ok(deliveries.len())
+}
+
+async fn record_attempt(pool: &PgPool, attempt: &Attempt) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Every statement below keys off the claimed delivery, so a worker whose claim ran out
+    // records nothing
+    let logged = sqlx::query!(
+        "INSERT INTO webhook_delivery_attempts (delivery_id, status_code, error, duration_ms)
+        SELECT id, $3, $4, $5 FROM webhook_deliveries
+        WHERE id = $1 AND status = 'pending' AND next_attempt_at = $2
+        FOR UPDATE",
+        attempt.delivery_id,
+        attempt.claimed_until,
+        attempt.status_code,
+        attempt.error,
+        attempt.duration_ms
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if logged == 0 {
+        return Ok(());
+    }
+
+    let Some(error) = &attempt.error else {
+        sqlx::query!(
+            "UPDATE webhook_deliveries SET status = 'delivered', attempts = $1, last_status_code = $2,
+                last_error = NULL, delivered_at = NOW()
+            WHERE id = $3",
+            attempt.attempts,
+            attempt.status_code,
+            attempt.delivery_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if attempt.event != "ping" {
+            sqlx::query!(
+                "UPDATE webhooks SET consecutive_failures = 0 WHERE id = $1 AND active",
+                attempt.webhook_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        return tx.commit().await;
+    };
+
+    // Pings are not retried, and don't count towards disabling the webhook
+    let is_ping = attempt.event == "ping";
+    let failed = sqlx::query_scalar!(
+        r#"UPDATE webhook_deliveries SET attempts = $1::INT, last_status_code = $2, last_error = $3,
+            status = CASE WHEN $1 >= $4 OR $5 THEN 'failed' ELSE 'pending' END::webhook_delivery_status,
+            next_attempt_at = CASE WHEN $1 >= $4 OR $5 THEN next_attempt_at
+                ELSE NOW() + make_interval(mins => POWER(2, $1::INT - 1)::INT) END
+        WHERE id = $6
+        RETURNING status = 'failed' AS "failed!""#,
+        attempt.attempts,
+        attempt.status_code,
+        error,
+        MAX_ATTEMPTS,
+        is_ping,
+        attempt.delivery_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if failed && !is_ping {
+        let disabled = sqlx::query_scalar!(
+            r#"UPDATE webhooks SET consecutive_failures = consecutive_failures + 1,
+                active = consecutive_failures + 1 < $1,
+                disabled_at = CASE WHEN consecutive_failures + 1 >= $1 THEN NOW() END
+            WHERE id = $2 AND active
+            RETURNING NOT active AS "disabled!""#,
+            DISABLE_AFTER_FAILURES,
+            attempt.webhook_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if disabled == Some(true) {
+            eprintln!(
+                "Disabled webhook {} after {} failed deliveries",
+                attempt.webhook_id, DISABLE_AFTER_FAILURES
+            );
+        }
+    }
+
+    tx.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        sync::{
+            atomic::{AtomicU16, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use serde_json::json;
+    use sqlx::postgres::PgConnectOptions;
+
+    use super::*;
+    use crate::models::WebhookDeliveryStatus;
+
+    const SECRET: &str = "whsec_test";
+
+    #[derive(Default)]
+    struct Receiver {
+        status: AtomicU16,
+        requests: Mutex<Vec<(HeaderMap, Bytes)>>,
+    }
+
+    /// Runs `test` against a database of its own with every migration applied, and drops it
+    /// afterwards. Needs `DATABASE_URL`, like the build.
+    async fn with_database<F, Fut>(test: F)
+    where
+        F: FnOnce(PgPool) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let options: PgConnectOptions = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set to run the tests")
+            .parse()
+            .unwrap();
+        let admin = PgPool::connect_with(options.clone()).await.unwrap();
+        let name = format!("todo_test_{}", Uuid::new_v4().simple());
+        sqlx::query(&format!(r#"CREATE DATABASE "{}""#, name))
+            .execute(&admin)
+            .await
+            .unwrap();
+
+        let pool = PgPool::connect_with(options.database(&name)).await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let result = tokio::spawn(test(pool.clone())).await;
+        pool.close().await;
+
+        sqlx::query(&format!(r#"DROP DATABASE "{}" WITH (FORCE)"#, name))
+            .execute(&admin)
+            .await
+            .unwrap();
+        if let Err(err) = result {
+            std::panic::resume_unwind(err.into_panic());
+        }
+    }
+
+    /// A receiver on localhost answering every delivery with `status`.
+    async fn start_receiver(status: u16) -> (Arc<Receiver>, String) {
+        let receiver = Arc::new(Receiver {
+            status: AtomicU16::new(status),
+            ..Default::default()
+        });
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State(receiver): State<Arc<Receiver>>, headers: HeaderMap, body: Bytes| async move {
+                        receiver.requests.lock().unwrap().push((headers, body));
+                        StatusCode::from_u16(receiver.status.load(Ordering::SeqCst)).unwrap()
+                    },
+                ),
+            )
+            .with_state(receiver.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (receiver, url)
+    }
+
+    /// Registers a webhook for `url` with one delivery queued. Returns both ids.
+    async fn queue_delivery(pool: &PgPool, url: &str) -> (Uuid, Uuid) {
+        let user_id = sqlx::query_scalar!(
+            "INSERT INTO users (username, email, password) VALUES ('hooks', 'hooks@example.com', 'x') RETURNING id"
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let webhook_id = sqlx::query_scalar!(
+            "INSERT INTO webhooks (user_id, url, secret, events) VALUES ($1, $2, $3, ARRAY['list.created']) RETURNING id",
+            user_id,
+            url,
+            SECRET
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let delivery_id = sqlx::query_scalar!(
+            "INSERT INTO webhook_deliveries (webhook_id, event, payload) VALUES ($1, 'list.created', $2) RETURNING id",
+            webhook_id,
+            json!({"event": "list.created", "data": {"list": {"title": "Hook me"}}})
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        (webhook_id, delivery_id)
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_payloads() {
+        with_database(delivers_signed_payloads_test).await;
+    }
+
+    async fn delivers_signed_payloads_test(pool: PgPool) {
+        let (receiver, url) = start_receiver(204).await;
+        let (_, delivery_id) = queue_delivery(&pool, &url).await;
+
+        assert_eq!(deliver_webhooks(&pool, true).await.unwrap(), 1);
+
+        let requests = receiver.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let (headers, body) = &requests[0];
+        let header = |name: &str| headers[name].to_str().unwrap().to_string();
+        let timestamp: i64 = header("X-Webhook-Timestamp").parse().unwrap();
+        assert_eq!(
+            header("X-Webhook-Signature"),
+            format!("sha256={}", sign_webhook(SECRET, timestamp, body))
+        );
+        assert_eq!(header("X-Webhook-Id"), delivery_id.to_string());
+        assert_eq!(header("X-Webhook-Event"), "list.created");
+
+        let delivery = sqlx::query!(
+            r#"SELECT status AS "status: WebhookDeliveryStatus", attempts, last_status_code,
+                (SELECT COUNT(*) FROM webhook_delivery_attempts WHERE delivery_id = $1) AS "logged!"
+            FROM webhook_deliveries WHERE id = $1"#,
+            delivery_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.last_status_code, Some(204));
+        assert_eq!(delivery.logged, 1);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_deliveries_with_backoff() {
+        with_database(retries_failed_deliveries_with_backoff_test).await;
+    }
+
+    async fn retries_failed_deliveries_with_backoff_test(pool: PgPool) {
+        let (receiver, url) = start_receiver(500).await;
+        let (_, delivery_id) = queue_delivery(&pool, &url).await;
+
+        for (attempts, backoff_minutes) in [(1, 1.0), (2, 2.0), (3, 4.0)] {
+            assert_eq!(deliver_webhooks(&pool, true).await.unwrap(), 1);
+
+            let delivery = sqlx::query!(
+                r#"SELECT status AS "status: WebhookDeliveryStatus", attempts, last_status_code,
+                    EXTRACT(EPOCH FROM next_attempt_at - NOW())::FLOAT8 AS "wait!"
+                FROM webhook_deliveries WHERE id = $1"#,
+                delivery_id
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+            assert_eq!(delivery.attempts, attempts);
+            assert_eq!(delivery.last_status_code, Some(500));
+            assert!((delivery.wait - backoff_minutes * 60.0).abs() < 10.0);
+
+            // Not due yet, so nothing is sent until the clock is wound forward
+            assert_eq!(deliver_webhooks(&pool, true).await.unwrap(), 0);
+            sqlx::query!(
+                "UPDATE webhook_deliveries SET next_attempt_at = NOW() WHERE id = $1",
+                delivery_id
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(receiver.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn disables_webhooks_that_keep_failing() {
+        with_database(disables_webhooks_that_keep_failing_test).await;
+    }
+
+    async fn disables_webhooks_that_keep_failing_test(pool: PgPool) {
+        let (_, url) = start_receiver(500).await;
+        let (webhook_id, delivery_id) = queue_delivery(&pool, &url).await;
+        sqlx::query!(
+            "UPDATE webhook_deliveries SET attempts = $1 WHERE id = $2",
+            MAX_ATTEMPTS - 1,
+            delivery_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            "UPDATE webhooks SET consecutive_failures = $1 WHERE id = $2",
+            DISABLE_AFTER_FAILURES - 1,
+            webhook_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(deliver_webhooks(&pool, true).await.unwrap(), 1);
+
+        let status = sqlx::query_scalar!(
+            r#"SELECT status AS "status: WebhookDeliveryStatus" FROM webhook_deliveries WHERE id = $1"#,
+            delivery_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(status, WebhookDeliveryStatus::Failed);
+
+        let webhook = sqlx::query!(
+            "SELECT active, consecutive_failures, disabled_at FROM webhooks WHERE id = $1",
+            webhook_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(!webhook.active);
+        assert_eq!(webhook.consecutive_failures, DISABLE_AFTER_FAILURES);
+        assert!(webhook.disabled_at.is_some());
+    }
+}
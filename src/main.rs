@@ -1,8 +1,11 @@
+mod config;
 mod handlers;
+mod jobs;
 mod middlewares;
 mod models;
 mod routes;
 mod schemas;
+mod storage;
 mod utils;
 
 use std::sync::Arc;
@@ -11,13 +14,23 @@ use axum::http::{
     header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
     Method,
 };
+use config::Config;
 use dotenv::dotenv;
+use models::{ListChangeNotice, StreamUsers};
 use routes::create_router;
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use storage::Storage;
+use tokio::sync::broadcast;
 use tower_http::cors::CorsLayer;
 
 pub struct AppState {
     db: Pool<Postgres>,
+    config: Config,
+    storage: Arc<dyn Storage>,
+    /// Item changes from every server, for open event streams.
+    changes: broadcast::Sender<ListChangeNotice>,
+    /// Users with an event stream open on this server.
+    stream_users: StreamUsers,
 }
 
 #[tokio::main]
@@ -53,7 +66,29 @@ async fn main() {
         .allow_credentials(true)
         .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
 
-    let server = create_router(Arc::new(AppState { db: pool.clone() })).layer(cors);
+    let config = Config::init();
+    jobs::spawn_trash_purge(pool.clone(), config.clone());
+    jobs::spawn_auto_archive(pool.clone(), config.clone());
+    jobs::spawn_idempotency_cleanup(pool.clone());
+    jobs::spawn_due_soon_notifications(pool.clone(), config.clone());
+    jobs::spawn_reminder_delivery(pool.clone());
+    jobs::spawn_digest_delivery(pool.clone());
+    jobs::spawn_webhook_delivery(pool.clone(), config.clone());
+    jobs::spawn_sync_cleanup(pool.clone(), config.clone());
+    let (changes, _) = broadcast::channel(1024);
+    let stream_users = StreamUsers::default();
+    jobs::spawn_list_change_listener(pool.clone(), changes.clone(), stream_users.clone());
+    let storage = storage::init();
+    jobs::spawn_orphaned_files_cleanup(pool.clone(), storage.clone());
+
+    let server = create_router(Arc::new(AppState {
+        db: pool.clone(),
+        config,
+        storage,
+        changes,
+        stream_users,
+    }))
+    .layer(cors);
 
     println!("🚀 Server started successfully");
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8083").await.unwrap();
@@ -0,0 +1,263 @@
+use super::workspace_middleware::WORKSPACE_HEADER;
+use crate::{handlers::upload_body_limit, models::UserModel, AppState};
+use axum::{
+    body::{to_bytes, Body},
+    extract::State,
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// A request still holding its key after this long is assumed to have died, and a retry takes
+/// the key over instead of being told the request is still being processed.
+const CLAIM_TIMEOUT_SECONDS: f64 = 60.0;
+
+/// Makes POST requests carrying an `Idempotency-Key` header safe to retry. The first response for
+/// a user and key is stored for 24 hours and replayed for retries of the same request; reusing the
+/// key for a different request is rejected. Must run after `authorize_user`.
+pub async fn idempotent_request(
+    State(data): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    idempotent(data, req, next, MAX_BODY_SIZE).await
+}
+
+/// `idempotent_request` for attachment uploads, which buffers up to a full batch of files.
+pub async fn idempotent_upload(
+    State(data): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let max_body_size = upload_body_limit(&data.config);
+    idempotent(data, req, next, max_body_size).await
+}
+
+async fn idempotent(
+    data: Arc<AppState>,
+    req: Request<Body>,
+    next: Next,
+    max_body_size: usize,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let (Some(key), Some(current_user)) = (
+        req.headers().get(IDEMPOTENCY_KEY),
+        req.extensions().get::<UserModel>(),
+    ) else {
+        return Ok(next.run(req).await);
+    };
+    if req.method() != Method::POST {
+        return Ok(next.run(req).await);
+    }
+
+    let key = key
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= 255)
+        .ok_or_else(|| {
+            let error_response = json!({"status": "fail", "message": "Idempotency-Key must be 1-255 visible characters"});
+            (StatusCode::BAD_REQUEST, Json(error_response))
+        })?
+        .to_string();
+    let user_id = current_user.id;
+
+    let internal_error = |err: sqlx::Error| {
+        let error_response = json!({"status": "fail", "message": format!("Cannot process Idempotency-Key: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, max_body_size).await.map_err(|_| {
+        let error_response =
+            json!({"status": "fail", "message": "Request body is too large for an idempotent request"});
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(error_response))
+    })?;
+
+    // Everything that decides what the request does, so that a reused key never replays the
+    // response to another request, e.g. one made in a different workspace
+    let mut hasher = Sha256::new();
+    hasher.update(parts.method.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(
+        parts
+            .uri
+            .path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or_default()
+            .as_bytes(),
+    );
+    hasher.update(b"\n");
+    hasher.update(
+        parts
+            .headers
+            .get(WORKSPACE_HEADER)
+            .map(|value| value.as_bytes())
+            .unwrap_or_default(),
+    );
+    hasher.update(b"\n");
+    hasher.update(&body);
+    let request_hash = hasher.finalize().to_vec();
+
+    sqlx::query!(
+        "DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2 AND created_at < NOW() - INTERVAL '24 hours'",
+        user_id,
+        key
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let locked_at = sqlx::query_scalar!(
+        r#"INSERT INTO idempotency_keys (user_id, key, request_hash, locked_at) VALUES ($1, $2, $3, NOW())
+        ON CONFLICT (user_id, key) DO UPDATE SET locked_at = NOW(), created_at = NOW()
+        WHERE idempotency_keys.request_hash = EXCLUDED.request_hash AND idempotency_keys.status_code IS NULL
+            AND COALESCE(idempotency_keys.locked_at, idempotency_keys.created_at) < NOW() - make_interval(secs => $4)
+        RETURNING locked_at AS "locked_at!""#,
+        user_id,
+        key,
+        request_hash,
+        CLAIM_TIMEOUT_SECONDS
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(locked_at) = locked_at else {
+        let stored = sqlx::query!(
+            "SELECT request_hash, status_code, response_body, content_type FROM idempotency_keys WHERE user_id = $1 AND key = $2",
+            user_id,
+            key
+        )
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+        return match stored {
+            Some(stored) if stored.request_hash != request_hash => {
+                let error_response = json!({"status": "fail", "message": "This Idempotency-Key was already used for a different request"});
+                Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error_response)))
+            }
+            Some(stored) => match (stored.status_code, stored.response_body) {
+                (Some(status_code), Some(response_body)) => {
+                    let mut response = Response::builder()
+                        .status(status_code as u16)
+                        .header("idempotent-replayed", "true");
+                    if let Some(content_type) = stored.content_type {
+                        response = response.header(header::CONTENT_TYPE, content_type);
+                    }
+                    Ok(response.body(Body::from(response_body)).unwrap())
+                }
+                _ => {
+                    let error_response = json!({"status": "fail", "message": "A request with this Idempotency-Key is still being processed"});
+                    Err((StatusCode::CONFLICT, Json(error_response)))
+                }
+            },
+            None => {
+                let error_response = json!({"status": "fail", "message": "A request with this Idempotency-Key is still being processed"});
+                Err((StatusCode::CONFLICT, Json(error_response)))
+            }
+        };
+    };
+    let mut claim = KeyClaim {
+        pool: data.db.clone(),
+        user_id,
+        key,
+        locked_at,
+        released: false,
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) if !parts.status.is_server_error() => body,
+        // Server errors aren't stored so that retrying can actually succeed
+        result => {
+            release_key(&data.db, &user_id, &claim.key, &locked_at)
+                .await
+                .map_err(internal_error)?;
+            claim.released = true;
+
+            return Ok(Response::from_parts(
+                parts,
+                Body::from(result.unwrap_or_default()),
+            ));
+        }
+    };
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    sqlx::query!(
+        "UPDATE idempotency_keys SET status_code = $3, response_body = $4, content_type = $5, locked_at = NULL
+        WHERE user_id = $1 AND key = $2 AND locked_at = $6",
+        user_id,
+        claim.key,
+        parts.status.as_u16() as i16,
+        body.as_ref(),
+        content_type,
+        locked_at
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+    claim.released = true;
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Gives the key back when the request never gets to store its response, e.g. because the client
+/// disconnected and the handler was dropped, or it panicked, so retries don't have to wait for
+/// the claim to time out.
+struct KeyClaim {
+    pool: PgPool,
+    user_id: Uuid,
+    key: String,
+    locked_at: DateTime<Utc>,
+    released: bool,
+}
+
+impl Drop for KeyClaim {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        let pool = self.pool.clone();
+        let (user_id, key, locked_at) =
+            (self.user_id, std::mem::take(&mut self.key), self.locked_at);
+        tokio::spawn(async move {
+            if let Err(err) = release_key(&pool, &user_id, &key, &locked_at).await {
+                eprintln!("Cannot release Idempotency-Key {:?}: {:?}", key, err);
+            }
+        });
+    }
+}
+
+/// Only deletes the claim this request made, not one a retry has taken over since.
+async fn release_key(
+    pool: &PgPool,
+    user_id: &Uuid,
+    key: &str,
+    locked_at: &DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2 AND locked_at = $3",
+        user_id,
+        key,
+        locked_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
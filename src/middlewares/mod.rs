@@ -1,3 +1,7 @@
 mod authorization_middleware;
+mod idempotency_middleware;
+mod workspace_middleware;
 
-pub use authorization_middleware::{authorize_user};
\ No newline at end of file
+pub use authorization_middleware::{authorize_user};
+pub use idempotency_middleware::{idempotent_request, idempotent_upload};
+pub use workspace_middleware::select_workspace;
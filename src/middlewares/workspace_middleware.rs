@@ -0,0 +1,73 @@
+use crate::{
+    models::{CurrentWorkspace, UserModel, WorkspaceRole},
+    AppState,
+};
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+pub const WORKSPACE_HEADER: &str = "x-workspace-id";
+
+/// Picks the workspace a request works in: the `:workspace_id` path segment, else the
+/// `X-Workspace-Id` header, else the user's personal workspace. Users who aren't a member get a
+/// 404. Must run after `authorize_user`.
+pub async fn select_workspace(
+    State(data): State<Arc<AppState>>,
+    path: Option<Path<HashMap<String, String>>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let Some(current_user) = req.extensions().get::<UserModel>().cloned() else {
+        return Ok(next.run(req).await);
+    };
+
+    let requested = path
+        .as_ref()
+        .and_then(|Path(params)| params.get("workspace_id").cloned())
+        .or_else(|| {
+            req.headers()
+                .get(WORKSPACE_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+        });
+    let workspace_id = match requested {
+        Some(value) => Some(value.parse::<Uuid>().map_err(|_| {
+            let error_response =
+                json!({"status": "fail", "message": format!("Invalid workspace id '{}'", value)});
+            (StatusCode::BAD_REQUEST, Json(error_response))
+        })?),
+        None => None,
+    };
+
+    let workspace = sqlx::query_as!(
+        CurrentWorkspace,
+        r#"SELECT w.id, w.personal, m.role AS "role: WorkspaceRole"
+        FROM workspaces w JOIN workspace_members m ON m.workspace_id = w.id AND m.user_id = $1
+        WHERE ($2::uuid IS NULL AND w.personal AND w.created_by = $1) OR w.id = $2"#,
+        current_user.id,
+        workspace_id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|err| {
+        let error_response =
+            json!({"status": "fail", "message": format!("Cannot load the workspace: {:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?;
+
+    let Some(workspace) = workspace else {
+        let error_response = json!({"status": "fail", "message": "Workspace not found"});
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    req.extensions_mut().insert(workspace);
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::utils::render_markdown;
+
+/// A comment on a list item, with its author's username and, for the first comment of a thread,
+/// its replies.
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct CommentModel {
+    pub id: Uuid,
+    pub list_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub parent_id: Option<Uuid>,
+    pub body: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "editedAt")]
+    pub edited_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Sanitized HTML rendering of `body`, only filled in for `?render=html`.
+    #[sqlx(skip)]
+    #[serde(rename = "bodyHtml", skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replies: Option<Vec<CommentModel>>,
+}
+
+impl CommentModel {
+    pub fn render_body(&mut self) {
+        self.body_html = Some(render_markdown(&self.body));
+        for reply in self.replies.iter_mut().flatten() {
+            reply.render_body();
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "digest_frequency", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct DigestModel {
+    pub user_id: Uuid,
+    pub frequency: DigestFrequency,
+    #[serde(rename = "sendTime")]
+    pub send_time: chrono::NaiveTime,
+    /// ISO day of the week for weekly digests, 1 is Monday.
+    pub weekday: Option<i16>,
+    pub timezone: String,
+    #[serde(rename = "nextSendAt")]
+    pub next_send_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "lastSentAt")]
+    pub last_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
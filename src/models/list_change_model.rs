@@ -0,0 +1,70 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A change to an item, as announced on the `list_changes` channel.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct ListChangeModel {
+    pub event: String,
+    pub list_id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_id: Option<Uuid>,
+    #[serde(rename = "actorId")]
+    pub actor_id: Option<Uuid>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// What the change listener passes on to open event streams.
+#[derive(Debug, Clone)]
+pub enum ListChangeNotice {
+    /// The change, with whichever users streaming from this server can see the item.
+    Change(ListChangeModel, Arc<HashSet<Uuid>>),
+    /// Changes may have been missed, e.g. while the listener reconnected; clients should refetch.
+    Resync,
+}
+
+/// Users with an event stream open on this server, so the change listener checks who can see
+/// an item once per change rather than once per stream.
+#[derive(Debug, Clone, Default)]
+pub struct StreamUsers(Arc<Mutex<HashMap<Uuid, usize>>>);
+
+impl StreamUsers {
+    /// Counts the user as streaming until the returned guard is dropped.
+    pub fn register(&self, user_id: Uuid) -> StreamUserGuard {
+        *self.0.lock().unwrap().entry(user_id).or_default() += 1;
+        StreamUserGuard {
+            users: self.clone(),
+            user_id,
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<Uuid> {
+        self.0.lock().unwrap().keys().copied().collect()
+    }
+}
+
+pub struct StreamUserGuard {
+    users: StreamUsers,
+    user_id: Uuid,
+}
+
+impl Drop for StreamUserGuard {
+    fn drop(&mut self) {
+        let mut users = self.users.0.lock().unwrap();
+        if let Some(count) = users.get_mut(&self.user_id) {
+            *count -= 1;
+            if *count == 0 {
+                users.remove(&self.user_id);
+            }
+        }
+    }
+}
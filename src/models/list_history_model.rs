@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One change to a list item; `changes` maps each changed field to `{"old": ..., "new": ...}`.
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct ListHistoryModel {
+    pub id: Uuid,
+    pub list_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub changes: serde_json::Value,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -2,6 +2,41 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use super::{ListAssigneeModel, TagModel};
+use crate::utils::render_markdown;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize, sqlx::Type,
+)]
+#[sqlx(type_name = "importance_level", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Importance {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Importance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Importance::Low => "low",
+            Importance::Medium => "medium",
+            Importance::High => "high",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "list_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ListStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Done,
+}
+
 #[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
 #[allow(non_snake_case)]
 pub struct ListModel {
@@ -10,9 +45,97 @@ pub struct ListModel {
     pub user_id: Uuid,
     pub descr: Option<String>,
     pub body: Option<String>,
-    pub importance: String, // high, medium , low
+    pub importance: Importance,
+    pub position: f64,
+    pub version: i32,
+    pub status: ListStatus,
+    pub project_id: Option<Uuid>,
+    pub workspace_id: Uuid,
+    #[serde(rename = "dueAt")]
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(rename = "createdAt")]
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "archivedAt")]
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub tags: Vec<TagModel>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub assignees: Vec<ListAssigneeModel>,
+    /// Only selected when searching with `q`.
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f32>,
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Sanitized HTML rendering of `body`, only filled in for `?render=html`.
+    #[sqlx(skip)]
+    #[serde(rename = "bodyHtml", skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
+}
+
+/// A `lists` row as the checked `query_as!` macros return it, without the tags, assignees and
+/// search fields that are filled in afterwards.
+#[derive(Debug)]
+pub struct ListRow {
+    pub id: Uuid,
+    pub title: String,
+    pub user_id: Uuid,
+    pub descr: Option<String>,
+    pub body: Option<String>,
+    pub importance: Importance,
+    pub position: f64,
+    pub version: i32,
+    pub status: ListStatus,
+    pub project_id: Option<Uuid>,
+    pub workspace_id: Uuid,
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<ListRow> for ListModel {
+    fn from(row: ListRow) -> Self {
+        ListModel {
+            id: row.id,
+            title: row.title,
+            user_id: row.user_id,
+            descr: row.descr,
+            body: row.body,
+            importance: row.importance,
+            position: row.position,
+            version: row.version,
+            status: row.status,
+            project_id: row.project_id,
+            workspace_id: row.workspace_id,
+            due_at: row.due_at,
+            completed_at: row.completed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            archived_at: row.archived_at,
+            deleted_at: row.deleted_at,
+            tags: Vec::new(),
+            assignees: Vec::new(),
+            rank: None,
+            snippet: None,
+            body_html: None,
+        }
+    }
+}
+
+impl ListModel {
+    pub fn render_body(&mut self) {
+        self.body_html = Some(render_markdown(self.body.as_deref().unwrap_or_default()));
+    }
 }
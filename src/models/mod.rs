@@ -1,7 +1,38 @@
+mod attachment_model;
+mod comment_model;
+mod digest_model;
+mod list_assignee_model;
+mod list_change_model;
+mod list_history_model;
 mod list_model;
+mod notification_model;
 mod otp_model;
+mod project_model;
+mod reminder_model;
+mod share_model;
+mod tag_model;
 mod user_model;
+mod webhook_model;
+mod workspace_model;
 
-pub use list_model::ListModel;
+pub use attachment_model::AttachmentModel;
+pub use comment_model::CommentModel;
+pub use digest_model::{DigestFrequency, DigestModel};
+pub use list_assignee_model::ListAssigneeModel;
+pub use list_change_model::{ListChangeModel, ListChangeNotice, StreamUserGuard, StreamUsers};
+pub use list_history_model::ListHistoryModel;
+pub use list_model::{Importance, ListModel, ListRow, ListStatus};
+pub use notification_model::{NotificationModel, NotificationType};
 pub use otp_model::OtpModel;
+pub use project_model::ProjectModel;
+pub use reminder_model::{ReminderModel, ReminderStatus};
+pub use share_model::{ShareModel, ShareRole};
+pub use tag_model::TagModel;
 pub use user_model::UserModel;
+pub use webhook_model::{
+    WebhookDeliveryAttemptModel, WebhookDeliveryModel, WebhookDeliveryStatus, WebhookModel,
+    WEBHOOK_EVENTS,
+};
+pub use workspace_model::{
+    CurrentWorkspace, WorkspaceInvitationModel, WorkspaceMemberModel, WorkspaceModel, WorkspaceRole,
+};
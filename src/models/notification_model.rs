@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "notification_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
+    Assigned,
+    Mentioned,
+    DueSoon,
+    Shared,
+}
+
+impl NotificationType {
+    pub const ALL: [NotificationType; 4] = [
+        NotificationType::Assigned,
+        NotificationType::Mentioned,
+        NotificationType::DueSoon,
+        NotificationType::Shared,
+    ];
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct NotificationModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(rename = "type")]
+    pub kind: NotificationType,
+    pub actor_id: Option<Uuid>,
+    pub list_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub comment_id: Option<Uuid>,
+    pub message: String,
+    #[serde(rename = "readAt")]
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "reminder_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderStatus {
+    Pending,
+    Sent,
+    Failed,
+    /// The item was done, deleted or no longer visible to the user when the reminder came up.
+    Skipped,
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct ReminderModel {
+    pub id: Uuid,
+    pub list_id: Uuid,
+    pub user_id: Uuid,
+    #[serde(rename = "remindAt")]
+    pub remind_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "offsetMinutes")]
+    pub offset_minutes: Option<i32>,
+    #[serde(rename = "fireAt")]
+    pub fire_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub status: ReminderStatus,
+    pub attempts: i32,
+    #[serde(rename = "retryAt")]
+    pub retry_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "sentAt")]
+    pub sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Events a webhook can subscribe to. `ping` is only sent on request and needs no subscription.
+pub const WEBHOOK_EVENTS: [&str; 12] = [
+    "list.created",
+    "list.updated",
+    "list.completed",
+    "list.deleted",
+    "list.restored",
+    "list.archived",
+    "list.unarchived",
+    "comment.created",
+    "user.updated",
+    "user.password_changed",
+    "user.avatar_updated",
+    "user.email_verified",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// Every attempt failed.
+    Failed,
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct WebhookModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    #[serde(rename = "consecutiveFailures")]
+    pub consecutive_failures: i32,
+    #[serde(rename = "disabledAt")]
+    pub disabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct WebhookDeliveryModel {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "lastStatusCode")]
+    pub last_status_code: Option<i32>,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "deliveredAt")]
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, FromRow, Deserialize, Serialize, Clone)]
+#[allow(non_snake_case)]
+pub struct WebhookDeliveryAttemptModel {
+    pub id: Uuid,
+    pub delivery_id: Uuid,
+    #[serde(rename = "statusCode")]
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: i32,
+    #[serde(rename = "createdAt")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
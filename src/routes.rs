@@ -1,13 +1,37 @@
 use crate::{
     handlers::{
-        add_list_handler, create_user_handler, delete_list_handler, get_list_by_id_handler,
-        get_user_by_username, get_users_lists_handler, health_checker_handler, login_handler,
-        update_list_handler, update_password, upload_img, verify_email,
+        accept_invitation_handler, add_comment_handler, add_list_handler, add_project_handler,
+        add_reminder_handler, add_tag_handler, add_webhook_handler, add_workspace_handler,
+        archive_list_handler, assign_list_handler, attach_tags_handler, bulk_lists_handler,
+        create_event_ticket_handler, create_user_handler, delete_attachment_handler,
+        delete_comment_handler, delete_digest_handler, delete_list_handler, delete_project_handler,
+        delete_reminder_handler, delete_tag_handler, delete_webhook_handler,
+        delete_workspace_handler, detach_tags_handler, download_attachment_handler,
+        empty_trash_handler, event_stream_handler, get_assignees_handler, get_attachments_handler,
+        get_comments_handler, get_digest_handler, get_invitations_handler, get_list_by_id_handler,
+        get_list_history_handler, get_list_shares_handler, get_members_handler,
+        get_notification_preferences_handler, get_notifications_handler,
+        get_project_shares_handler, get_projects_handler, get_reminders_handler, get_sync_handler,
+        get_tags_handler, get_trash_handler, get_user_by_username, get_users_lists_handler,
+        get_webhook_deliveries_handler, get_webhook_delivery_handler, get_webhook_handler,
+        get_webhooks_handler, get_workspace_handler, get_workspaces_handler,
+        health_checker_handler, invite_member_handler, login_handler,
+        mark_all_notifications_read_handler, mark_notification_read_handler,
+        mark_notification_unread_handler, merge_tags_handler, move_list_handler,
+        patch_list_handler, ping_webhook_handler, push_sync_handler, remove_member_handler,
+        restore_list_handler, revert_list_handler, revoke_invitation_handler, share_list_handler,
+        share_project_handler, unarchive_list_handler, unassign_list_handler, unshare_list_handler,
+        unshare_project_handler, update_comment_handler, update_digest_handler,
+        update_list_handler, update_member_handler, update_notification_preferences_handler,
+        update_password, update_project_handler, update_tag_handler, update_webhook_handler,
+        update_workspace_handler, upload_attachments_handler, upload_body_limit, upload_img,
+        verify_email,
     },
-    middlewares::authorize_user,
+    middlewares::{authorize_user, idempotent_request, idempotent_upload, select_workspace},
     AppState,
 };
 use axum::{
+    extract::DefaultBodyLimit,
     middleware::from_fn_with_state,
     routing::{delete, get, patch, post},
     Router,
@@ -15,6 +39,8 @@ use axum::{
 use std::sync::Arc;
 
 pub fn create_router(app_state: Arc<AppState>) -> Router {
+    let upload_limit = upload_body_limit(&app_state.config);
+
     Router::new()
         .route("/api/health_checker", get(health_checker_handler))
         .route("/api/user/register", post(create_user_handler))
@@ -34,16 +60,21 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
         )
         .route(
             "/api/lists/list",
-            post(add_list_handler).layer(from_fn_with_state(app_state.clone(), authorize_user)),
+            post(add_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
         )
         .route(
             "/api/lists",
             get(get_users_lists_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
                 .layer(from_fn_with_state(app_state.clone(), authorize_user)),
         )
         .route(
             "/api/lists/:id",
             get(get_list_by_id_handler)
+                .patch(patch_list_handler)
                 .layer(from_fn_with_state(app_state.clone(), authorize_user)),
         )
         .route(
@@ -55,5 +86,326 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             delete(delete_list_handler)
                 .layer(from_fn_with_state(app_state.clone(), authorize_user)),
         )
+        .route(
+            "/api/lists/bulk",
+            post(bulk_lists_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/sync",
+            get(get_sync_handler)
+                .post(push_sync_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/trash",
+            get(get_trash_handler)
+                .delete(empty_trash_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/restore",
+            post(restore_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/archive",
+            post(archive_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/unarchive",
+            post(unarchive_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/history",
+            get(get_list_history_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/history/:revision_id/revert",
+            post(revert_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/attachments",
+            get(get_attachments_handler)
+                .post(upload_attachments_handler)
+                .layer(DefaultBodyLimit::max(upload_limit))
+                .layer(from_fn_with_state(app_state.clone(), idempotent_upload))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/attachments/:attachment_id",
+            get(download_attachment_handler)
+                .delete(delete_attachment_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/shares",
+            get(get_list_shares_handler)
+                .post(share_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/shares/:user_id",
+            delete(unshare_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/assignees",
+            get(get_assignees_handler)
+                .post(assign_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/assignees/:user_id",
+            delete(unassign_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/comments",
+            get(get_comments_handler)
+                .post(add_comment_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/comments/:comment_id",
+            patch(update_comment_handler)
+                .delete(delete_comment_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/reminders",
+            get(get_reminders_handler)
+                .post(add_reminder_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/reminders/:reminder_id",
+            delete(delete_reminder_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/lists/:id/move",
+            post(move_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/projects",
+            get(get_projects_handler)
+                .post(add_project_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/projects/:id",
+            patch(update_project_handler)
+                .delete(delete_project_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/projects/:id/shares",
+            get(get_project_shares_handler)
+                .post(share_project_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/projects/:id/shares/:user_id",
+            delete(unshare_project_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces",
+            get(get_workspaces_handler)
+                .post(add_workspace_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id",
+            get(get_workspace_handler)
+                .patch(update_workspace_handler)
+                .delete(delete_workspace_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/members",
+            get(get_members_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/members/:user_id",
+            patch(update_member_handler)
+                .delete(remove_member_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/invitations",
+            get(get_invitations_handler)
+                .post(invite_member_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/invitations/:invitation_id",
+            delete(revoke_invitation_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/lists",
+            get(get_users_lists_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/lists/list",
+            post(add_list_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/lists/trash",
+            get(get_trash_handler)
+                .delete(empty_trash_handler)
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/workspaces/:workspace_id/projects",
+            get(get_projects_handler)
+                .post(add_project_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), select_workspace))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/invitations/accept",
+            post(accept_invitation_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/notifications",
+            get(get_notifications_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/notifications/read_all",
+            post(mark_all_notifications_read_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/notifications/preferences",
+            get(get_notification_preferences_handler)
+                .patch(update_notification_preferences_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/notifications/digest",
+            get(get_digest_handler)
+                .put(update_digest_handler)
+                .delete(delete_digest_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/notifications/:id/read",
+            post(mark_notification_read_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/notifications/:id/unread",
+            post(mark_notification_unread_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route("/api/events", get(event_stream_handler))
+        .route(
+            "/api/events/ticket",
+            post(create_event_ticket_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/webhooks",
+            get(get_webhooks_handler)
+                .post(add_webhook_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/webhooks/:id",
+            get(get_webhook_handler)
+                .patch(update_webhook_handler)
+                .delete(delete_webhook_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/webhooks/:id/ping",
+            post(ping_webhook_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/webhooks/:id/deliveries",
+            get(get_webhook_deliveries_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/webhooks/:id/deliveries/:delivery_id",
+            get(get_webhook_delivery_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/tags",
+            get(get_tags_handler)
+                .post(add_tag_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/tags/merge",
+            post(merge_tags_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/tags/attach",
+            post(attach_tags_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/tags/detach",
+            post(detach_tags_handler)
+                .layer(from_fn_with_state(app_state.clone(), idempotent_request))
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
+        .route(
+            "/api/tags/:id",
+            patch(update_tag_handler)
+                .delete(delete_tag_handler)
+                .layer(from_fn_with_state(app_state.clone(), authorize_user)),
+        )
         .with_state(app_state)
 }
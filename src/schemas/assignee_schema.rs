@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+/// Usernames or emails of the people to assign. Assigning someone twice is a no-op.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignListSchema {
+    pub users: Vec<String>,
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{Importance, ListStatus};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// Any failing item rolls back the whole request.
+    #[default]
+    Atomic,
+    /// Failing items are skipped, everything else is kept.
+    BestEffort,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkListSchema {
+    #[serde(default)]
+    pub mode: BulkMode,
+    pub operations: Vec<BulkOperation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkOperation {
+    Update {
+        ids: Vec<Uuid>,
+        fields: BulkUpdateFields,
+    },
+    Complete {
+        ids: Vec<Uuid>,
+    },
+    /// `project_id: null` takes the items out of their project.
+    MoveToProject {
+        ids: Vec<Uuid>,
+        project_id: Option<Uuid>,
+    },
+    Tag {
+        ids: Vec<Uuid>,
+        tag_ids: Vec<Uuid>,
+    },
+    Untag {
+        ids: Vec<Uuid>,
+        tag_ids: Vec<Uuid>,
+    },
+    Archive {
+        ids: Vec<Uuid>,
+    },
+    Delete {
+        ids: Vec<Uuid>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkUpdateFields {
+    pub title: Option<String>,
+    pub descr: Option<String>,
+    pub body: Option<String>,
+    pub importance: Option<Importance>,
+    pub status: Option<ListStatus>,
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkItemStatus {
+    Ok,
+    Failed,
+    /// Succeeded, but undone because another item failed in atomic mode.
+    RolledBack,
+    /// Not attempted because an earlier item failed in atomic mode.
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkItemResult {
+    pub operation: usize,
+    pub op: &'static str,
+    pub id: Uuid,
+    pub status: BulkItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl BulkOperation {
+    pub fn ids(&self) -> &[Uuid] {
+        match self {
+            BulkOperation::Update { ids, .. }
+            | BulkOperation::Complete { ids }
+            | BulkOperation::MoveToProject { ids, .. }
+            | BulkOperation::Tag { ids, .. }
+            | BulkOperation::Untag { ids, .. }
+            | BulkOperation::Archive { ids }
+            | BulkOperation::Delete { ids } => ids,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BulkOperation::Update { .. } => "update",
+            BulkOperation::Complete { .. } => "complete",
+            BulkOperation::MoveToProject { .. } => "move_to_project",
+            BulkOperation::Tag { .. } => "tag",
+            BulkOperation::Untag { .. } => "untag",
+            BulkOperation::Archive { .. } => "archive",
+            BulkOperation::Delete { .. } => "delete",
+        }
+    }
+}
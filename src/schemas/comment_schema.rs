@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// `parent_id` replies to an existing comment; replies to a reply join the same thread.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCommentSchema {
+    pub body: String,
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCommentSchema {
+    pub body: String,
+}
+
+/// Pages through threads, oldest first; every thread comes with all of its replies.
+#[derive(Debug, Default, Deserialize)]
+pub struct CommentQuery {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    pub render: Option<String>,
+}
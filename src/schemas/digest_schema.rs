@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::DigestFrequency;
+
+/// `send_time` is a local time of day such as `"08:00"` in `timezone`, an IANA name like
+/// `"Europe/Berlin"`. Weekly digests also need a `weekday`, 1 (Monday) to 7 (Sunday).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateDigestSchema {
+    pub frequency: DigestFrequency,
+    pub send_time: chrono::NaiveTime,
+    pub weekday: Option<i16>,
+    pub timezone: String,
+}
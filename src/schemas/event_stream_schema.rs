@@ -0,0 +1,6 @@
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventStreamQuery {
+    pub ticket: Option<String>,
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::Patch;
+use crate::models::{Importance, ListStatus, TagModel};
+
 #[derive(Serialize)]
 pub struct ListResponse {
     pub id: Uuid,
@@ -8,9 +11,17 @@ pub struct ListResponse {
     pub descr: Option<String>,
     pub body: Option<String>,
     pub user_id: Uuid,
-    pub importance: String, // high, medium , low
+    pub importance: Importance,
+    pub position: f64,
+    pub version: i32,
+    pub status: ListStatus,
+    pub project_id: Option<Uuid>,
+    pub workspace_id: Uuid,
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub tags: Vec<TagModel>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,22 +29,36 @@ pub struct CreateListSchema {
     pub title: String,
     pub descr: Option<String>,
     pub body: Option<String>,
-    pub importance: String, // high, medium , low
+    pub importance: Importance,
+    pub status: Option<ListStatus>,
+    pub project_id: Option<Uuid>,
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Deserialize)]
-pub struct PaginationSchema {
-    pub page: Option<usize>,
-    pub page_size: Option<usize>,
-    pub search_title: Option<String>,
+/// Partial update of a list item: fields left out are kept, `null` clears nullable fields.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateListSchema {
+    #[serde(default)]
+    pub title: Patch<String>,
+    #[serde(default)]
+    pub descr: Patch<String>,
+    #[serde(default)]
+    pub body: Patch<String>,
+    #[serde(default)]
+    pub importance: Patch<Importance>,
+    #[serde(default)]
+    pub status: Patch<ListStatus>,
+    #[serde(default)]
+    pub project_id: Patch<Uuid>,
+    #[serde(default)]
+    pub due_at: Patch<chrono::DateTime<chrono::Utc>>,
+    /// Only read by `PATCH /api/lists/list`; `PATCH /api/lists/:id` takes it from the path.
+    pub id: Option<Uuid>,
 }
 
+/// Places an item between two neighbours; with only one side given it lands directly next to that item.
 #[derive(Serialize, Deserialize, Debug)]
-pub struct UpdateListSchema {
-    pub title: Option<String>,
-    pub descr: Option<String>,
-    pub body: Option<String>,
-    pub importance: Option<String>, // high, medium , low
-    pub id: Uuid,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+pub struct MoveListSchema {
+    pub after_id: Option<Uuid>,
+    pub before_id: Option<Uuid>,
 }
@@ -1,9 +1,42 @@
+mod assignee_schema;
+mod bulk_schema;
+mod comment_schema;
+mod digest_schema;
+mod event_stream_schema;
 mod list_schema;
+mod notification_schema;
 mod otp_schema;
+mod patch_schema;
+mod project_schema;
+mod reminder_schema;
+mod share_schema;
+mod sync_schema;
+mod tag_schema;
 mod user_schema;
+mod webhook_schema;
+mod workspace_schema;
 
-pub use list_schema::{CreateListSchema, ListResponse, PaginationSchema, UpdateListSchema};
+pub use assignee_schema::AssignListSchema;
+pub use bulk_schema::{BulkItemResult, BulkItemStatus, BulkListSchema, BulkMode, BulkOperation};
+pub use comment_schema::{CommentQuery, CreateCommentSchema, UpdateCommentSchema};
+pub use digest_schema::UpdateDigestSchema;
+pub use event_stream_schema::EventStreamQuery;
+pub use list_schema::{CreateListSchema, ListResponse, MoveListSchema, UpdateListSchema};
+pub use notification_schema::{NotificationQuery, UpdateNotificationPreferencesSchema};
 pub use otp_schema::OtpSchema;
+pub use patch_schema::Patch;
+pub use project_schema::{CreateProjectSchema, UpdateProjectSchema};
+pub use reminder_schema::CreateReminderSchema;
+pub use share_schema::CreateShareSchema;
+pub use sync_schema::{
+    SyncChange, SyncChangeResult, SyncChangeStatus, SyncConflictMode, SyncPushSchema, SyncQuery,
+};
+pub use tag_schema::{CreateTagSchema, MergeTagsSchema, TagAssignmentSchema, UpdateTagSchema};
 pub use user_schema::{
     CreateUserSchema, LoginSchema, UpdatePasswordSchema, UserResponse, VerifyEmailSchema,
 };
+pub use webhook_schema::{CreateWebhookSchema, UpdateWebhookSchema, WebhookDeliveryQuery};
+pub use workspace_schema::{
+    AcceptInvitationSchema, CreateInvitationSchema, CreateWorkspaceSchema, UpdateMemberSchema,
+    UpdateWorkspaceSchema,
+};
@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::NotificationType;
+
+/// Newest first; `unread=true` leaves out what was already read.
+#[derive(Debug, Default, Deserialize)]
+pub struct NotificationQuery {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    pub unread: Option<bool>,
+}
+
+/// Turns emails on or off per event type, e.g. `{"email": {"mentioned": false}}`. Types left
+/// out keep their current setting.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateNotificationPreferencesSchema {
+    pub email: HashMap<NotificationType, bool>,
+}
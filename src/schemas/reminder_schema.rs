@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// Exactly one of `remind_at` (a fixed time) or `offset_minutes` (how long before the item is due).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReminderSchema {
+    pub remind_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub offset_minutes: Option<i32>,
+}
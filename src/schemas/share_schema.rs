@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::ShareRole;
+
+/// `user` is the username or email of the person to share with. Sharing again changes their role.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateShareSchema {
+    pub user: String,
+    pub role: ShareRole,
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{CreateListSchema, UpdateListSchema};
+use crate::models::ListModel;
+
+/// Without `since`, every item the user can see comes back as created.
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncQuery {
+    pub since: Option<String>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncConflictMode {
+    /// Changes made against an outdated version are not applied; the result carries the item as
+    /// it is now.
+    #[default]
+    Reject,
+    /// Changes are applied whatever the version, field by field, so the last write wins.
+    Overwrite,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncPushSchema {
+    #[serde(default)]
+    pub on_conflict: SyncConflictMode,
+    pub changes: Vec<SyncChange>,
+}
+
+/// Items are created with ids chosen by the client, so pushing the same change twice is harmless.
+/// `base_version` is the version the client last saw; leave it out to skip the conflict check.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncChange {
+    Create {
+        id: Uuid,
+        fields: CreateListSchema,
+    },
+    Update {
+        id: Uuid,
+        base_version: Option<i32>,
+        fields: UpdateListSchema,
+    },
+    Delete {
+        id: Uuid,
+        base_version: Option<i32>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncChangeStatus {
+    Applied,
+    /// The item changed since `base_version` and the change was rejected.
+    Conflict,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncChangeResult {
+    pub index: usize,
+    pub op: &'static str,
+    pub id: Uuid,
+    pub status: SyncChangeStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The item after the change, or as it is now for conflicts; missing once it is deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list: Option<ListModel>,
+}
+
+impl SyncChange {
+    pub fn id(&self) -> Uuid {
+        match self {
+            SyncChange::Create { id, .. }
+            | SyncChange::Update { id, .. }
+            | SyncChange::Delete { id, .. } => *id,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SyncChange::Create { .. } => "create",
+            SyncChange::Update { .. } => "update",
+            SyncChange::Delete { .. } => "delete",
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTagSchema {
+    pub name: String,
+    pub color: Option<String>, // hex, e.g. #ff9800
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTagSchema {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeTagsSchema {
+    pub source_ids: Vec<Uuid>,
+    pub target_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagAssignmentSchema {
+    pub list_ids: Vec<Uuid>,
+    pub tag_ids: Vec<Uuid>,
+}
@@ -1,17 +1,6 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Deserialize, Debug, Default)]
-pub struct FilterOptions{
-    pub page: Option<usize>,
-    pub limit: Option<usize>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct ParamOptions {
-    pub id: String,
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateUserSchema {
     pub username: String,
@@ -37,14 +26,6 @@ pub struct UserResponse {
 
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct  UpdateUserSchema{
-    pub email: Option<String>,
-    pub password: Option<String>,
-    pub img: Option<String>,
-}
-
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyEmailSchema {
     pub email: String,
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateWebhookSchema {
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+/// Setting `active` to true turns a disabled webhook back on and resets its failure count.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateWebhookSchema {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+/// Newest first.
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
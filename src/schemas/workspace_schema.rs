@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::WorkspaceRole;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateWorkspaceSchema {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateWorkspaceSchema {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateMemberSchema {
+    pub role: WorkspaceRole,
+}
+
+/// Inviting an address again replaces its open invitation, with a fresh link.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateInvitationSchema {
+    pub email: String,
+    pub role: WorkspaceRole,
+}
+
+/// `token` is the one from the link in the invitation email.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AcceptInvitationSchema {
+    pub token: String,
+}
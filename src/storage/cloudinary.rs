@@ -0,0 +1,137 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::Utc;
+use reqwest::multipart::{Form, Part};
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+
+use super::{required_env, Storage};
+
+/// Stores files as private raw assets on Cloudinary, using the same `CLOUDINARY_*` account
+/// settings as avatar uploads. Private assets can only be fetched through signed API calls.
+pub struct CloudinaryStorage {
+    client: reqwest::Client,
+    cloud_name: String,
+    api_key: String,
+    api_secret: String,
+}
+
+impl CloudinaryStorage {
+    pub fn from_env() -> CloudinaryStorage {
+        CloudinaryStorage {
+            client: reqwest::Client::new(),
+            cloud_name: required_env("CLOUDINARY_CLOUD_NAME"),
+            api_key: required_env("CLOUDINARY_API_KEY"),
+            api_secret: required_env("CLOUDINARY_API_SECRET"),
+        }
+    }
+
+    fn url(&self, action: &str) -> String {
+        format!(
+            "https://api.cloudinary.com/v1_1/{}/raw/{}",
+            self.cloud_name, action
+        )
+    }
+
+    /// Adds `timestamp`, `api_key` and the signature Cloudinary expects: a SHA-1 of the
+    /// alphabetically sorted parameters followed by the API secret.
+    fn sign(&self, mut params: Vec<(&'static str, String)>) -> Vec<(&'static str, String)> {
+        params.push(("timestamp", Utc::now().timestamp().to_string()));
+        params.sort();
+
+        let to_sign = params
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signature = Sha1::digest(format!("{}{}", to_sign, self.api_secret).as_bytes());
+
+        params.push(("api_key", self.api_key.clone()));
+        params.push(("signature", format!("{:x}", signature)));
+        params
+    }
+}
+
+#[async_trait]
+impl Storage for CloudinaryStorage {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<()> {
+        let params = self.sign(vec![
+            ("public_id", key.to_string()),
+            ("type", "private".to_string()),
+        ]);
+        let file_name = key.rsplit('/').next().unwrap_or(key).to_string();
+        let mut form = Form::new().part(
+            "file",
+            Part::stream(data)
+                .file_name(file_name)
+                .mime_str(content_type)?,
+        );
+        for (name, value) in params {
+            form = form.text(name, value);
+        }
+
+        let response = self
+            .client
+            .post(self.url("upload"))
+            .multipart(form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!(
+                "Cloudinary upload failed with {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes> {
+        let params = self.sign(vec![
+            ("public_id", key.to_string()),
+            ("type", "private".to_string()),
+        ]);
+        let response = self
+            .client
+            .get(self.url("download"))
+            .query(&params)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!(
+                "Cloudinary download of '{}' failed with {}",
+                key,
+                response.status()
+            );
+        }
+        Ok(response.bytes().await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let params = self.sign(vec![
+            ("public_id", key.to_string()),
+            ("type", "private".to_string()),
+        ]);
+        let response = self
+            .client
+            .post(self.url("destroy"))
+            .form(&params)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!(
+                "Cloudinary delete of '{}' failed with {}",
+                key,
+                response.status()
+            );
+        }
+
+        // "not found" means there is nothing left to delete
+        let result: Value = response.json().await?;
+        match result["result"].as_str() {
+            Some("ok") | Some("not found") => Ok(()),
+            _ => bail!("Cloudinary could not delete '{}': {}", key, result),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::Bytes;
+
+use super::{check_key, Storage};
+
+/// Keeps files under `LOCAL_STORAGE_DIR` (default `uploads`), mirroring the key as a path.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn from_env() -> LocalStorage {
+        let root = std::env::var("LOCAL_STORAGE_DIR").unwrap_or_else(|_| "uploads".to_string());
+        LocalStorage { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, data: Bytes) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Renaming is atomic, so a crash mid-write never leaves a truncated file under the key
+        let partial_path = path.with_extension("partial");
+        tokio::fs::write(&partial_path, &data).await?;
+        tokio::fs::rename(&partial_path, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes> {
+        Ok(tokio::fs::read(self.path(key)?).await?.into())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            result => Ok(result?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn puts_gets_and_deletes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage {
+            root: dir.path().into(),
+        };
+        let key = "attachments/list/file";
+
+        storage
+            .put(key, "text/plain", Bytes::from_static(b"hello"))
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get(key).await.unwrap(),
+            Bytes::from_static(b"hello")
+        );
+        assert!(dir.path().join(key).is_file());
+        assert!(!dir.path().join("attachments/list/file.partial").exists());
+
+        storage.delete(key).await.unwrap();
+        assert!(storage.get(key).await.is_err());
+        // Already gone, which is fine
+        storage.delete(key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_keys_outside_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("uploads");
+        let storage = LocalStorage { root: root.clone() };
+        tokio::fs::write(dir.path().join("secret"), "keep out")
+            .await
+            .unwrap();
+
+        for key in [
+            "../secret",
+            "attachments/../../secret",
+            "/secret",
+            "attachments//secret",
+            "./secret",
+        ] {
+            let put = storage
+                .put(key, "text/plain", Bytes::from_static(b"overwritten"))
+                .await;
+            assert!(put.is_err(), "put accepted {key}");
+            assert!(storage.get(key).await.is_err(), "get accepted {key}");
+            assert!(storage.delete(key).await.is_err(), "delete accepted {key}");
+        }
+        assert_eq!(
+            tokio::fs::read_to_string(dir.path().join("secret"))
+                .await
+                .unwrap(),
+            "keep out"
+        );
+        assert!(!root.exists());
+    }
+}
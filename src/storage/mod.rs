@@ -0,0 +1,49 @@
+mod cloudinary;
+mod local;
+mod s3;
+
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+
+/// Where attachment files are kept. Keys are generated by the API
+/// (`attachments/<list id>/<attachment id>`), never taken from the client.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Bytes>;
+    /// Deleting a key that doesn't exist is not an error.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Picks the backend named by `STORAGE_BACKEND`: `local` (the default), `s3` or `cloudinary`.
+pub fn init() -> Arc<dyn Storage> {
+    let backend = std::env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+    match backend.as_str() {
+        "local" => Arc::new(local::LocalStorage::from_env()),
+        "s3" => Arc::new(s3::S3Storage::from_env()),
+        "cloudinary" => Arc::new(cloudinary::CloudinaryStorage::from_env()),
+        other => panic!(
+            "STORAGE_BACKEND must be local, s3 or cloudinary, not '{}'",
+            other
+        ),
+    }
+}
+
+/// Keys become paths in the local and S3 backends, so none may have empty, `.` or `..` segments
+/// that would lead outside the storage root or bucket.
+fn check_key(key: &str) -> Result<()> {
+    if key
+        .split('/')
+        .any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        bail!("Invalid storage key '{}'", key);
+    }
+    Ok(())
+}
+
+fn required_env(key: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| panic!("{} must have a value", key))
+}
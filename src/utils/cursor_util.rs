@@ -0,0 +1,13 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::signed_token_util::{sign_token, verify_token};
+
+/// Serializes the payload into an opaque, signed cursor.
+pub fn encode_cursor<T: Serialize>(payload: &T) -> String {
+    sign_token("cursor", payload)
+}
+
+/// Returns `None` for malformed or tampered cursors.
+pub fn decode_cursor<T: DeserializeOwned>(token: &str) -> Option<T> {
+    verify_token("cursor", token)
+}
@@ -1,3 +1,5 @@
+use super::{render_markdown, DigestEmail};
+use askama::Template;
 use lettre::{Message, SmtpTransport, Transport};
 use lettre::transport::smtp::authentication::Credentials;
 
@@ -29,8 +31,94 @@ pub async fn send_otp_mail(to: &str, otp: &str, username: &str){
 
     let subject = "Your OTP";
 
-    match email_sender(to, &subject, &html_body).await {
+    match email_sender(to, subject, &html_body).await {
         Ok(_) => println!("Otp email has been sent to {username}"),
         Err(e) => eprintln!("Failed to send email: {:?}", e),
     }
-}
\ No newline at end of file
+}
+
+pub async fn send_invitation_mail(to: &str, inviter: &str, workspace: &str, link: &str){
+    let subject = format!("Join {workspace}");
+
+    // Names are chosen by users, so they are escaped before going into the HTML
+    let inviter = ammonia::clean_text(inviter);
+    let workspace = ammonia::clean_text(workspace);
+    let html_body = format!("<h1>You're invited</h1> <br/> <p>{inviter} invited you to join <strong>{workspace}</strong>.</p> <p><a href=\"{link}\">Accept the invitation</a></p>");
+
+    match email_sender(to, &subject, &html_body).await {
+        Ok(_) => println!("Invitation email has been sent to {to}"),
+        Err(e) => eprintln!("Failed to send email: {:?}", e),
+    }
+}
+
+pub async fn send_assignment_mail(to: &str, assigner: &str, title: &str){
+    let subject = "You have been assigned a task";
+
+    let assigner = ammonia::clean_text(assigner);
+    let title = ammonia::clean_text(title);
+    let html_body = format!("<h1>New assignment</h1> <br/> <p>{assigner} assigned <strong>{title}</strong> to you.</p>");
+
+    match email_sender(to, subject, &html_body).await {
+        Ok(_) => println!("Assignment email has been sent to {to}"),
+        Err(e) => eprintln!("Failed to send email: {:?}", e),
+    }
+}
+
+pub async fn send_mention_mail(to: &str, author: &str, title: &str, comment: &str){
+    let subject = format!("{author} mentioned you");
+
+    let author = ammonia::clean_text(author);
+    let title = ammonia::clean_text(title);
+    let html_body = format!("<h1>You were mentioned</h1> <br/> <p>{author} mentioned you in a comment on <strong>{title}</strong>:</p> {}", render_markdown(comment));
+
+    match email_sender(to, &subject, &html_body).await {
+        Ok(_) => println!("Mention email has been sent to {to}"),
+        Err(e) => eprintln!("Failed to send email: {:?}", e),
+    }
+}
+
+pub async fn send_share_mail(to: &str, sharer: &str, noun: &str, name: &str, role: &str){
+    let subject = format!("{sharer} shared a {noun} with you");
+
+    let sharer = ammonia::clean_text(sharer);
+    let name = ammonia::clean_text(name);
+    let html_body = format!("<h1>Shared with you</h1> <br/> <p>{sharer} shared the {noun} <strong>{name}</strong> with you as {role}.</p>");
+
+    match email_sender(to, &subject, &html_body).await {
+        Ok(_) => println!("Share email has been sent to {to}"),
+        Err(e) => eprintln!("Failed to send email: {:?}", e),
+    }
+}
+
+pub async fn send_due_soon_mail(to: &str, title: &str, due_at: &str){
+    let subject = format!("Due soon: {title}");
+
+    let title = ammonia::clean_text(title);
+    let html_body = format!("<h1>Coming up</h1> <br/> <p><strong>{title}</strong> is due {due_at}.</p>");
+
+    match email_sender(to, &subject, &html_body).await {
+        Ok(_) => println!("Due soon email has been sent to {to}"),
+        Err(e) => eprintln!("Failed to send email: {:?}", e),
+    }
+}
+
+/// Unlike the other emails, failures are returned so the caller can record and retry them.
+pub async fn send_reminder_mail(to: &str, title: &str, due_at: Option<&str>) -> Result<(), Box<dyn std::error::Error>>{
+    let subject = format!("Reminder: {title}");
+
+    let title = ammonia::clean_text(title);
+    let html_body = match due_at {
+        Some(due_at) => format!("<h1>Reminder</h1> <br/> <p><strong>{title}</strong> is due {due_at}.</p>"),
+        None => format!("<h1>Reminder</h1> <br/> <p>Don't forget <strong>{title}</strong>.</p>"),
+    };
+
+    email_sender(to, &subject, &html_body).await
+}
+
+/// Failures are returned, like reminders, so the digest can be retried.
+pub async fn send_digest_mail(to: &str, digest: &DigestEmail) -> Result<(), Box<dyn std::error::Error>>{
+    let subject = format!("Your {} digest", digest.period);
+    let html_body = digest.render()?;
+
+    email_sender(to, &subject, &html_body).await
+}
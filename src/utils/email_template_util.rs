@@ -0,0 +1,44 @@
+use askama::Template;
+
+/// Most items listed under one heading; the rest are only counted.
+pub const MAX_DIGEST_SECTION_ITEMS: usize = 20;
+
+/// Templates under `templates/` are compiled in, and values are HTML-escaped when rendered.
+#[derive(Template)]
+#[template(path = "emails/digest.html")]
+pub struct DigestEmail {
+    pub username: String,
+    /// "daily" or "weekly".
+    pub period: String,
+    /// Only headings with something under them.
+    pub sections: Vec<DigestSection>,
+}
+
+pub struct DigestSection {
+    pub heading: &'static str,
+    pub items: Vec<DigestItem>,
+    pub total: usize,
+}
+
+pub struct DigestItem {
+    pub title: String,
+    /// When it is or was due, or when it was completed, in the user's timezone.
+    pub when: String,
+}
+
+impl DigestSection {
+    pub fn new(heading: &'static str) -> Self {
+        DigestSection {
+            heading,
+            items: Vec::new(),
+            total: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: DigestItem) {
+        if self.items.len() < MAX_DIGEST_SECTION_ITEMS {
+            self.items.push(item);
+        }
+        self.total += 1;
+    }
+}
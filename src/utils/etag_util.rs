@@ -0,0 +1,28 @@
+use axum::http::{header::IF_MATCH, HeaderMap};
+
+pub fn list_etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Versions accepted by an `If-Match` header, or `None` when any version will do
+/// (no header, or `*`). Tags that aren't ours simply never match, and neither do weak (`W/`)
+/// tags, since `If-Match` only allows strong comparison.
+pub fn if_match_versions(headers: &HeaderMap) -> Option<Vec<i32>> {
+    let value = headers.get(IF_MATCH)?.to_str().unwrap_or_default().trim();
+    if value == "*" {
+        return None;
+    }
+
+    Some(
+        value
+            .split(',')
+            .filter_map(|tag| {
+                tag.trim()
+                    .strip_prefix('"')?
+                    .strip_suffix('"')?
+                    .parse()
+                    .ok()
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,993 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use super::{decode_cursor, encode_cursor, parse_render};
+use crate::models::{CurrentWorkspace, ListModel, WorkspaceRole};
+
+const ALLOWED_PARAMS: &[&str] = &[
+    "page",
+    "page_size",
+    "pagination",
+    "cursor",
+    "include_total",
+    "include_archived",
+    "only_archived",
+    "sort",
+    "q",
+    "render",
+    "search_title",
+    "shared",
+    "assignee",
+    "importance",
+    "status",
+    "project",
+    "tag",
+    "tags_any",
+    "tags_all",
+    "created_after",
+    "created_before",
+    "updated_after",
+    "updated_before",
+    "due_after",
+    "due_before",
+];
+
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Keeps the page's OFFSET within a Postgres BIGINT whatever the page size.
+const MAX_PAGE: usize = i64::MAX as usize / MAX_PAGE_SIZE;
+
+/// Minimum trigram word similarity for an item to show up in fuzzy search results. Applied
+/// through `pg_trgm.word_similarity_threshold`, which the `<%` operator compares against.
+const FUZZY_THRESHOLD: f32 = 0.3;
+
+const HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MinWords=10, MaxWords=30, MaxFragments=2, FragmentDelimiter=\" ... \"";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Created,
+    Updated,
+    Due,
+    Priority,
+    Title,
+    Position,
+    Relevance,
+}
+
+impl SortField {
+    fn parse(name: &str) -> Option<SortField> {
+        match name {
+            "created" => Some(SortField::Created),
+            "updated" => Some(SortField::Updated),
+            "due" => Some(SortField::Due),
+            "priority" => Some(SortField::Priority),
+            "title" => Some(SortField::Title),
+            "position" => Some(SortField::Position),
+            "relevance" => Some(SortField::Relevance),
+            _ => None,
+        }
+    }
+
+    /// Direction used when the client names a field without `:asc` or `:desc`.
+    fn default_descending(self) -> bool {
+        matches!(
+            self,
+            SortField::Created | SortField::Updated | SortField::Priority | SortField::Relevance
+        )
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SortField::Created => "created",
+            SortField::Updated => "updated",
+            SortField::Due => "due",
+            SortField::Priority => "priority",
+            SortField::Title => "title",
+            SortField::Position => "position",
+            SortField::Relevance => "relevance",
+        }
+    }
+
+    /// SQL expression sorted on. Items without a due date always come last, in either direction.
+    fn expression(self, descending: bool) -> &'static str {
+        match self {
+            SortField::Created => "created_at",
+            SortField::Updated => "updated_at",
+            SortField::Due if descending => "COALESCE(due_at, '-infinity'::timestamptz)",
+            SortField::Due => "COALESCE(due_at, 'infinity'::timestamptz)",
+            SortField::Priority => "importance",
+            SortField::Title => "LOWER(title)",
+            SortField::Position => "position",
+            SortField::Relevance => "search_rank",
+        }
+    }
+
+    /// Binds a cursor value so it compares against `expression`.
+    fn push_cursor_value(
+        self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        descending: bool,
+        value: Option<String>,
+    ) {
+        match self {
+            SortField::Created | SortField::Updated => {
+                builder
+                    .push("CAST(")
+                    .push_bind(value)
+                    .push(" AS timestamptz)");
+            }
+            SortField::Due => {
+                let missing = if descending {
+                    "'-infinity'"
+                } else {
+                    "'infinity'"
+                };
+                builder
+                    .push("COALESCE(CAST(")
+                    .push_bind(value)
+                    .push(" AS timestamptz), ")
+                    .push(missing)
+                    .push("::timestamptz)");
+            }
+            SortField::Priority => {
+                builder
+                    .push("CAST(")
+                    .push_bind(value)
+                    .push(" AS importance_level)");
+            }
+            SortField::Title => {
+                builder
+                    .push("LOWER(CAST(")
+                    .push_bind(value)
+                    .push(" AS text))");
+            }
+            SortField::Position => {
+                builder
+                    .push("CAST(")
+                    .push_bind(value)
+                    .push(" AS double precision)");
+            }
+            SortField::Relevance => {
+                builder.push("CAST(").push_bind(value).push(" AS real)");
+            }
+        }
+    }
+
+    fn cursor_value(self, list: &ListModel) -> Option<String> {
+        let timestamp = |value: Option<DateTime<Utc>>| {
+            value.map(|value| value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        };
+
+        match self {
+            SortField::Created => timestamp(list.created_at),
+            SortField::Updated => timestamp(list.updated_at),
+            SortField::Due => timestamp(list.due_at),
+            SortField::Priority => Some(list.importance.as_str().to_string()),
+            SortField::Title => Some(list.title.clone()),
+            SortField::Position => Some(list.position.to_string()),
+            SortField::Relevance => list.rank.map(|rank| rank.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CursorDirection {
+    #[serde(rename = "next")]
+    Next,
+    #[serde(rename = "prev")]
+    Prev,
+}
+
+/// Position of a row in a keyset-paginated listing, handed out as a signed opaque token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListCursor {
+    #[serde(rename = "d")]
+    pub direction: CursorDirection,
+    #[serde(rename = "s")]
+    sort: String,
+    #[serde(rename = "k")]
+    keys: Vec<Option<String>>,
+    #[serde(rename = "i")]
+    id: Uuid,
+    /// Set once a search has fallen back to trigram matching, so later pages keep using it.
+    #[serde(rename = "f", default)]
+    fuzzy: bool,
+}
+
+/// Parsed `q` parameter.
+///
+/// Supports `"quoted phrases"`, `prefix*` matching and `-excluded` terms, e.g.
+/// `q=-draft "release notes" deploy*`. Terms are reduced to plain words before being
+/// assembled into a `to_tsquery` expression, so user input can never inject query syntax.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    tsquery: String,
+    /// The non-excluded words, used for trigram matching when the full-text search finds nothing.
+    words: String,
+    pub fuzzy: bool,
+}
+
+impl SearchQuery {
+    fn parse(value: &str) -> Result<SearchQuery, String> {
+        let mut terms: Vec<String> = Vec::new();
+        let mut words: Vec<String> = Vec::new();
+        let mut chars = value.chars().peekable();
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let Some(&first) = chars.peek() else {
+                break;
+            };
+
+            let excluded = first == '-';
+            if excluded {
+                chars.next();
+            }
+
+            let (term_words, prefix) = if chars.next_if_eq(&'"').is_some() {
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                (search_words(&phrase), false)
+            } else {
+                let mut token = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    token.push(c);
+                }
+                (search_words(&token), token.ends_with('*'))
+            };
+            if term_words.is_empty() {
+                continue;
+            }
+
+            let mut term = term_words.join(" <-> ");
+            if prefix {
+                term.push_str(":*");
+            }
+            if excluded {
+                terms.push(format!("!({})", term));
+            } else {
+                terms.push(format!("({})", term));
+                words.extend(term_words);
+            }
+        }
+
+        if words.is_empty() {
+            return Err("'q' must contain at least one word to search for".to_string());
+        }
+
+        Ok(SearchQuery {
+            tsquery: terms.join(" & "),
+            words: words.join(" "),
+            fuzzy: false,
+        })
+    }
+
+    fn mode(&self) -> &'static str {
+        if self.fuzzy {
+            "fuzzy"
+        } else {
+            "fulltext"
+        }
+    }
+}
+
+/// Archived items are hidden from the main view but still turn up in searches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArchivedFilter {
+    #[default]
+    Exclude,
+    Include,
+    Only,
+}
+
+/// Whose items to list: the user's own, the ones others shared with them (directly or through a
+/// project), or both. Defaults to `Mine` in the personal workspace and `All` in team workspaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharedFilter {
+    Mine,
+    WithMe,
+    All,
+}
+
+/// `assignee=me` or `assignee=<username>`. Only narrows down the items the user can already see.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssigneeFilter {
+    Me,
+    User(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ProjectFilter {
+    Project(Uuid),
+    NoProject,
+}
+
+/// Validated form of the `/api/lists` query string.
+#[derive(Debug, Default)]
+pub struct ListQuery {
+    pub page: usize,
+    pub page_size: usize,
+    pub cursor_mode: bool,
+    pub cursor: Option<ListCursor>,
+    pub include_total: bool,
+    pub sort: Vec<SortKey>,
+    pub search: Option<SearchQuery>,
+    pub search_title: Option<String>,
+    pub archived: ArchivedFilter,
+    pub shared: Option<SharedFilter>,
+    pub assignee: Option<AssigneeFilter>,
+    pub importance: Option<Vec<String>>,
+    pub status: Option<Vec<String>>,
+    pub project: Option<ProjectFilter>,
+    pub tags_any: Option<Vec<String>>,
+    pub tags_all: Option<Vec<String>>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    pub due_after: Option<DateTime<Utc>>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub render_html: bool,
+}
+
+impl ListQuery {
+    pub fn parse(params: &HashMap<String, String>) -> Result<ListQuery, String> {
+        let mut unknown: Vec<&str> = params
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !ALLOWED_PARAMS.contains(key))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(format!(
+                "Unknown query parameter(s): {}. Allowed: {}",
+                unknown.join(", "),
+                ALLOWED_PARAMS.join(", ")
+            ));
+        }
+
+        let get = |key: &str| {
+            params
+                .get(key)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+
+        let page = match get("page") {
+            Some(value) => parse_number("page", value, 1, MAX_PAGE)?,
+            None => 1,
+        };
+        let page_size = match get("page_size") {
+            Some(value) => parse_number("page_size", value, 1, MAX_PAGE_SIZE)?,
+            None => 10,
+        };
+
+        let cursor_mode = match get("pagination") {
+            None => get("cursor").is_some(),
+            Some("offset") if get("cursor").is_some() => {
+                return Err("'cursor' cannot be used with pagination=offset".to_string())
+            }
+            Some("offset") => false,
+            Some("cursor") => true,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid pagination '{}', expected offset or cursor",
+                    other
+                ))
+            }
+        };
+        if cursor_mode && get("page").is_some() {
+            return Err("'page' cannot be used with cursor pagination".to_string());
+        }
+
+        let include_total = match get("include_total") {
+            None => !cursor_mode,
+            Some(value) => parse_bool("include_total", value)?,
+        };
+
+        let render_html = parse_render(get("render"))?;
+        let mut search = get("q").map(SearchQuery::parse).transpose()?;
+        let include_archived = get("include_archived")
+            .map(|value| parse_bool("include_archived", value))
+            .transpose()?;
+        let only_archived = get("only_archived")
+            .map(|value| parse_bool("only_archived", value))
+            .transpose()?;
+        let shared = match get("shared") {
+            None => None,
+            Some("mine") => Some(SharedFilter::Mine),
+            Some("with_me") => Some(SharedFilter::WithMe),
+            Some("all") => Some(SharedFilter::All),
+            Some(other) => {
+                return Err(format!(
+                    "Invalid shared '{}', expected mine, with_me or all",
+                    other
+                ))
+            }
+        };
+        let archived = match (include_archived, only_archived) {
+            (Some(false), Some(true)) => {
+                return Err(
+                    "'include_archived=false' cannot be used with 'only_archived=true'".to_string(),
+                )
+            }
+            (_, Some(true)) => ArchivedFilter::Only,
+            (Some(true), _) => ArchivedFilter::Include,
+            (None, _) if search.is_some() => ArchivedFilter::Include,
+            _ => ArchivedFilter::Exclude,
+        };
+        let sort = parse_sort(get("sort"), search.is_some())?;
+        let cursor = match get("cursor") {
+            Some(token) => {
+                let cursor: ListCursor =
+                    decode_cursor(token).ok_or_else(|| "Invalid or tampered cursor".to_string())?;
+                if cursor.sort != sort_signature(&sort) || cursor.keys.len() != sort.len() {
+                    return Err(
+                        "This cursor was issued for a different sort order, start again without a cursor"
+                            .to_string(),
+                    );
+                }
+                if let Some(search) = search.as_mut() {
+                    search.fuzzy = cursor.fuzzy;
+                }
+                Some(cursor)
+            }
+            None => None,
+        };
+
+        let mut tags_all = get("tags_all").map(parse_list).unwrap_or_default();
+        if let Some(tag) = get("tag") {
+            tags_all.push(tag.to_lowercase());
+        }
+        tags_all.sort();
+        tags_all.dedup();
+
+        Ok(ListQuery {
+            page,
+            page_size,
+            cursor_mode,
+            cursor,
+            include_total,
+            sort,
+            search,
+            search_title: get("search_title").map(str::to_string),
+            archived,
+            shared,
+            assignee: get("assignee").map(|value| match value {
+                "me" => AssigneeFilter::Me,
+                username => AssigneeFilter::User(username.to_string()),
+            }),
+            importance: get("importance")
+                .map(|value| parse_enum_list("importance", value, &["low", "medium", "high"]))
+                .transpose()?,
+            status: get("status")
+                .map(|value| parse_enum_list("status", value, &["todo", "in_progress", "done"]))
+                .transpose()?,
+            project: get("project").map(parse_project).transpose()?,
+            tags_any: get("tags_any")
+                .map(parse_list)
+                .filter(|tags| !tags.is_empty()),
+            tags_all: Some(tags_all).filter(|tags| !tags.is_empty()),
+            created_after: get("created_after")
+                .map(|v| parse_date("created_after", v))
+                .transpose()?,
+            created_before: get("created_before")
+                .map(|v| parse_date("created_before", v))
+                .transpose()?,
+            updated_after: get("updated_after")
+                .map(|v| parse_date("updated_after", v))
+                .transpose()?,
+            updated_before: get("updated_before")
+                .map(|v| parse_date("updated_before", v))
+                .transpose()?,
+            due_after: get("due_after")
+                .map(|v| parse_date("due_after", v))
+                .transpose()?,
+            due_before: get("due_before")
+                .map(|v| parse_date("due_before", v))
+                .transpose()?,
+            render_html,
+        })
+    }
+
+    pub fn offset(&self) -> i64 {
+        if self.cursor_mode {
+            return 0;
+        }
+        ((self.page - 1) * self.page_size) as i64
+    }
+
+    /// True when the page is read backwards from a `prevCursor` and has to be reversed afterwards.
+    pub fn is_reversed(&self) -> bool {
+        matches!(&self.cursor, Some(cursor) if cursor.direction == CursorDirection::Prev)
+    }
+
+    /// `fulltext` or `fuzzy` when searching with `q`.
+    pub fn search_mode(&self) -> Option<&'static str> {
+        self.search.as_ref().map(SearchQuery::mode)
+    }
+
+    /// The `pg_trgm.word_similarity_threshold` to set for the query when it is a fuzzy search.
+    pub fn fuzzy_threshold(&self) -> Option<f32> {
+        self.search
+            .as_ref()
+            .filter(|search| search.fuzzy)
+            .map(|_| FUZZY_THRESHOLD)
+    }
+
+    /// A full-text search that found nothing on its first page is retried with trigram matching,
+    /// which tolerates typos. Returns false when there is nothing left to fall back to.
+    pub fn fall_back_to_fuzzy(&mut self) -> bool {
+        match self.search.as_mut() {
+            Some(search) if !search.fuzzy && self.cursor.is_none() && self.page == 1 => {
+                search.fuzzy = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn cursor_for(&self, list: &ListModel, direction: CursorDirection) -> String {
+        encode_cursor(&ListCursor {
+            direction,
+            sort: sort_signature(&self.sort),
+            keys: self
+                .sort
+                .iter()
+                .map(|key| key.field.cursor_value(list))
+                .collect(),
+            id: list.id,
+            fuzzy: self.search.as_ref().is_some_and(|search| search.fuzzy),
+        })
+    }
+
+    /// Appends `SELECT ... FROM ...`, adding `rank` and `snippet` columns when searching.
+    pub fn push_select(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        builder.push("SELECT lists.*");
+        match &self.search {
+            Some(search) if search.fuzzy => {
+                builder.push(", search_rank AS rank");
+            }
+            Some(_) => {
+                builder
+                    .push(", search_rank AS rank, ts_headline('english', COALESCE(NULLIF(CONCAT_WS(' ', descr, body), ''), title), search_query, ")
+                    .push_bind(HEADLINE_OPTIONS)
+                    .push(") AS snippet");
+            }
+            None => {}
+        }
+        self.push_from(builder);
+    }
+
+    pub fn push_count(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        builder.push("SELECT COUNT(*)");
+        self.push_from(builder);
+    }
+
+    /// Searching joins in `search_rank` (and `search_query` for full-text search) so that filters,
+    /// ordering and cursors can refer to them like ordinary columns.
+    fn push_from(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        builder.push(" FROM lists");
+        match &self.search {
+            Some(search) if search.fuzzy => {
+                builder
+                    .push(", LATERAL (SELECT GREATEST(word_similarity(")
+                    .push_bind(search.words.clone())
+                    .push(", title), word_similarity(")
+                    .push_bind(search.words.clone())
+                    .push(", COALESCE(descr, '')), word_similarity(")
+                    .push_bind(search.words.clone())
+                    .push(", COALESCE(body, ''))) AS search_rank) AS ranked");
+            }
+            Some(search) => {
+                builder
+                    .push(", to_tsquery('english', ")
+                    .push_bind(search.tsquery.clone())
+                    .push(") AS search_query, LATERAL (SELECT ts_rank_cd(search_vector, search_query) AS search_rank) AS ranked");
+            }
+            None => {}
+        }
+    }
+
+    /// Appends the keyset condition that starts the page right after (or before) the cursor row:
+    /// `(k1 > v1) OR (k1 = v1 AND k2 > v2) OR ... OR (k1 = v1 AND ... AND id > cursor_id)`,
+    /// with each comparison flipped for descending keys and for backward pages.
+    pub fn push_keyset(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        let Some(cursor) = &self.cursor else {
+            return;
+        };
+        let forward = cursor.direction == CursorDirection::Next;
+        let operator = |descending: bool| if descending != forward { " > " } else { " < " };
+
+        builder.push(" AND (");
+        for (index, key) in self.sort.iter().enumerate() {
+            builder.push("(");
+            self.push_equal_prefix(builder, cursor, index);
+            builder
+                .push(key.field.expression(key.descending))
+                .push(operator(key.descending));
+            key.field
+                .push_cursor_value(builder, key.descending, cursor.keys[index].clone());
+            builder.push(") OR ");
+        }
+        builder.push("(");
+        self.push_equal_prefix(builder, cursor, self.sort.len());
+        builder
+            .push("id")
+            .push(operator(false))
+            .push_bind(cursor.id)
+            .push("))");
+    }
+
+    fn push_equal_prefix(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        cursor: &ListCursor,
+        len: usize,
+    ) {
+        for (index, key) in self.sort.iter().take(len).enumerate() {
+            builder
+                .push(key.field.expression(key.descending))
+                .push(" = ");
+            key.field
+                .push_cursor_value(builder, key.descending, cursor.keys[index].clone());
+            builder.push(" AND ");
+        }
+    }
+
+    /// Appends `WHERE ...` for the current user and workspace and every requested filter, binding
+    /// all values.
+    pub fn push_filters(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        user_id: Uuid,
+        workspace: &CurrentWorkspace,
+    ) {
+        // Items assigned to someone are usually created by someone else, so look at everything by default
+        let shared = self
+            .shared
+            .unwrap_or(if workspace.personal && self.assignee.is_none() {
+                SharedFilter::Mine
+            } else {
+                SharedFilter::All
+            });
+
+        builder.push(" WHERE (");
+        if shared != SharedFilter::WithMe {
+            builder
+                .push("(workspace_id = ")
+                .push_bind(workspace.id)
+                .push(" AND user_id = ")
+                .push_bind(user_id)
+                .push(")");
+        }
+        if shared == SharedFilter::All {
+            builder.push(" OR ");
+        }
+        if shared != SharedFilter::Mine {
+            if workspace.personal {
+                // The personal workspace collects everything shared with the user from other workspaces
+                builder
+                    .push("(workspace_id <> ")
+                    .push_bind(workspace.id)
+                    .push(" AND ");
+                push_shared_with(builder, user_id);
+            } else {
+                builder
+                    .push("(workspace_id = ")
+                    .push_bind(workspace.id)
+                    .push(" AND user_id <> ")
+                    .push_bind(user_id);
+                if workspace.role < WorkspaceRole::Member {
+                    builder.push(" AND ");
+                    push_shared_with(builder, user_id);
+                }
+            }
+            builder.push(")");
+        }
+        builder.push(") AND deleted_at IS NULL");
+
+        match self.archived {
+            ArchivedFilter::Exclude => {
+                builder.push(" AND archived_at IS NULL");
+            }
+            ArchivedFilter::Only => {
+                builder.push(" AND archived_at IS NOT NULL");
+            }
+            ArchivedFilter::Include => {}
+        }
+
+        match &self.search {
+            // `<%` can use the trigram indexes, where comparing `search_rank` would read every row
+            Some(search) if search.fuzzy => {
+                builder
+                    .push(" AND (")
+                    .push_bind(search.words.clone())
+                    .push(" <% title OR ")
+                    .push_bind(search.words.clone())
+                    .push(" <% descr OR ")
+                    .push_bind(search.words.clone())
+                    .push(" <% body)");
+            }
+            Some(_) => {
+                builder.push(" AND search_vector @@ search_query");
+            }
+            None => {}
+        }
+
+        if let Some(search_title) = &self.search_title {
+            builder
+                .push(" AND title ILIKE ")
+                .push_bind(format!("%{}%", search_title));
+        }
+        if let Some(importance) = &self.importance {
+            builder
+                .push(" AND importance = ANY(CAST(")
+                .push_bind(importance.clone())
+                .push(" AS importance_level[]))");
+        }
+        if let Some(status) = &self.status {
+            builder
+                .push(" AND status = ANY(CAST(")
+                .push_bind(status.clone())
+                .push(" AS list_status[]))");
+        }
+        match &self.assignee {
+            Some(AssigneeFilter::Me) => {
+                builder
+                    .push(" AND lists.id IN (SELECT list_id FROM list_assignees WHERE user_id = ")
+                    .push_bind(user_id)
+                    .push(")");
+            }
+            Some(AssigneeFilter::User(username)) => {
+                builder
+                    .push(" AND lists.id IN (SELECT a.list_id FROM list_assignees a JOIN users u ON u.id = a.user_id WHERE u.username = ")
+                    .push_bind(username.clone())
+                    .push(")");
+            }
+            None => {}
+        }
+        match self.project {
+            Some(ProjectFilter::Project(project_id)) => {
+                builder.push(" AND project_id = ").push_bind(project_id);
+            }
+            Some(ProjectFilter::NoProject) => {
+                builder.push(" AND project_id IS NULL");
+            }
+            None => {}
+        }
+        if let Some(tags_any) = &self.tags_any {
+            builder
+                .push(" AND id IN (SELECT lt.list_id FROM list_tags lt JOIN tags t ON t.id = lt.tag_id WHERE LOWER(t.name) = ANY(")
+                .push_bind(tags_any.clone())
+                .push("))");
+        }
+        if let Some(tags_all) = &self.tags_all {
+            builder
+                .push(" AND (SELECT COUNT(DISTINCT LOWER(t.name)) FROM list_tags lt JOIN tags t ON t.id = lt.tag_id WHERE lt.list_id = lists.id AND LOWER(t.name) = ANY(")
+                .push_bind(tags_all.clone())
+                .push(")) = ")
+                .push_bind(tags_all.len() as i64);
+        }
+
+        let ranges = [
+            ("created_at >= ", self.created_after),
+            ("created_at < ", self.created_before),
+            ("updated_at >= ", self.updated_after),
+            ("updated_at < ", self.updated_before),
+            ("due_at >= ", self.due_after),
+            ("due_at < ", self.due_before),
+        ];
+        for (condition, value) in ranges {
+            if let Some(value) = value {
+                builder.push(" AND ").push(condition).push_bind(value);
+            }
+        }
+    }
+
+    /// Appends `ORDER BY ...` built only from whitelisted columns, with `id` as the final tiebreaker.
+    /// Backward cursor pages are read in the opposite order.
+    pub fn push_order_by(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        let reversed = self.is_reversed();
+        let direction = |descending: bool| {
+            if descending != reversed {
+                " DESC"
+            } else {
+                " ASC"
+            }
+        };
+
+        builder.push(" ORDER BY ");
+        for key in &self.sort {
+            builder
+                .push(key.field.expression(key.descending))
+                .push(direction(key.descending))
+                .push(", ");
+        }
+        builder.push("id").push(direction(false));
+    }
+}
+
+fn sort_signature(sort: &[SortKey]) -> String {
+    sort.iter()
+        .map(|key| {
+            format!(
+                "{}:{}",
+                key.field.name(),
+                if key.descending { "desc" } else { "asc" }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn parse_number(name: &str, value: &str, min: usize, max: usize) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(number) if number >= min && number <= max => Ok(number),
+        _ => Err(format!(
+            "'{}' must be a whole number between {} and {}",
+            name, min, max
+        )),
+    }
+}
+
+/// Items shared with the user one by one, assigned to them or in a project they own or that was
+/// shared with them. Mirrors the sharing part of the `list_role` SQL function, written out so the
+/// share indexes can be used; like `project_role`, creators only own projects in workspaces they
+/// are still a non-guest member of.
+fn push_shared_with(builder: &mut QueryBuilder<'_, Postgres>, user_id: Uuid) {
+    builder
+        .push("(lists.id IN (SELECT list_id FROM list_shares WHERE user_id = ")
+        .push_bind(user_id)
+        .push(" UNION ALL SELECT list_id FROM list_assignees WHERE user_id = ")
+        .push_bind(user_id)
+        .push(") OR project_id IN (SELECT id FROM projects WHERE user_id = ")
+        .push_bind(user_id)
+        .push(" AND workspace_id IN (SELECT workspace_id FROM workspace_members WHERE user_id = ")
+        .push_bind(user_id)
+        .push(" AND role <> 'guest')")
+        .push(" UNION ALL SELECT project_id FROM project_shares WHERE user_id = ")
+        .push_bind(user_id)
+        .push("))");
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(format!(
+            "Invalid {} '{}', expected true or false",
+            name, other
+        )),
+    }
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_enum_list(name: &str, value: &str, allowed: &[&str]) -> Result<Vec<String>, String> {
+    let values = parse_list(value);
+    match values
+        .iter()
+        .find(|value| !allowed.contains(&value.as_str()))
+    {
+        Some(invalid) => Err(format!(
+            "Invalid {} '{}', expected one of: {}",
+            name,
+            invalid,
+            allowed.join(", ")
+        )),
+        None => Ok(values),
+    }
+}
+
+fn parse_project(value: &str) -> Result<ProjectFilter, String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(ProjectFilter::NoProject);
+    }
+
+    Uuid::parse_str(value)
+        .map(ProjectFilter::Project)
+        .map_err(|_| {
+            format!(
+                "Invalid project '{}', expected a project id or 'none'",
+                value
+            )
+        })
+}
+
+/// Accepts full RFC 3339 timestamps or plain dates, which are read as midnight UTC.
+fn parse_date(name: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| {
+            format!(
+                "Invalid {} '{}', expected a date (YYYY-MM-DD) or an RFC 3339 timestamp",
+                name, value
+            )
+        })
+}
+
+/// Lowercased runs of letters and digits; everything else separates words.
+fn search_words(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Searches default to the most relevant results first.
+fn parse_sort(value: Option<&str>, searching: bool) -> Result<Vec<SortKey>, String> {
+    let mut keys: Vec<SortKey> = Vec::new();
+    for part in value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (name, direction) = match part.split_once(':') {
+            Some((name, direction)) => (name, Some(direction)),
+            None => (part, None),
+        };
+
+        let field = SortField::parse(name).ok_or_else(|| {
+            format!(
+                "Unknown sort field '{}', expected one of: created, updated, due, priority, title, position, relevance",
+                name
+            )
+        })?;
+        if field == SortField::Relevance && !searching {
+            return Err("Sorting by relevance requires a search query 'q'".to_string());
+        }
+        let descending = match direction {
+            None => field.default_descending(),
+            Some("asc") => false,
+            Some("desc") => true,
+            Some(other) => {
+                return Err(format!(
+                    "Invalid sort direction '{}' for '{}', expected asc or desc",
+                    other, name
+                ))
+            }
+        };
+
+        if keys.iter().any(|key| key.field == field) {
+            return Err(format!("Sort field '{}' is listed more than once", name));
+        }
+        keys.push(SortKey { field, descending });
+    }
+
+    if keys.is_empty() {
+        let field = if searching {
+            SortField::Relevance
+        } else {
+            SortField::Position
+        };
+        keys.push(SortKey {
+            field,
+            descending: field.default_descending(),
+        });
+    }
+
+    Ok(keys)
+}
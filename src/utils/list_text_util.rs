@@ -0,0 +1,53 @@
+use crate::config::Config;
+
+/// Checks the free-text fields of a list item against the configured limits. Fields passed as
+/// `None` are left unchanged by the request and aren't checked.
+pub fn validate_list_text(
+    config: &Config,
+    title: Option<&str>,
+    descr: Option<&str>,
+    body: Option<&str>,
+) -> Result<(), String> {
+    if let Some(title) = title {
+        if title.trim().is_empty() {
+            return Err("Title cannot be empty".to_string());
+        }
+    }
+
+    let fields = [
+        ("title", title, config.max_title_length),
+        ("descr", descr, config.max_descr_length),
+        ("body", body, config.max_body_length),
+    ];
+    for (name, value, max_length) in fields {
+        if value.is_some_and(|value| value.chars().count() > max_length) {
+            return Err(format!(
+                "'{}' cannot be longer than {} characters",
+                name, max_length
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Describes errors raised by the database's own checks on list items (length ceilings, values
+/// too large to index), which are the client's fault rather than the server's.
+pub fn list_constraint_message(err: &sqlx::Error) -> Option<String> {
+    let db_error = err.as_database_error()?;
+    match db_error.code()?.as_ref() {
+        // check_violation
+        "23514" => Some(match db_error.constraint() {
+            Some("lists_title_length") => "'title' is too long".to_string(),
+            Some("lists_descr_length") => "'descr' is too long".to_string(),
+            Some("lists_body_length") => "'body' is too long".to_string(),
+            Some("lists_project_workspace") => {
+                "The project belongs to another workspace".to_string()
+            }
+            _ => "The item is not valid".to_string(),
+        }),
+        // string_data_right_truncation, program_limit_exceeded (e.g. a body too large to index)
+        "22001" | "54000" => Some("The item's text is too long".to_string()),
+        _ => None,
+    }
+}
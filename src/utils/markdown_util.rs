@@ -0,0 +1,34 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Turns a Markdown body into HTML that is safe to inject into a page: raw HTML in the source
+/// is kept only as far as ammonia's allow-list permits, and scripts, event handlers and
+/// `javascript:` links are stripped.
+pub fn render_markdown(source: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let parser = Parser::new_ext(source, options);
+
+    let mut unsafe_html = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::Builder::default()
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Reads the `render` query parameter: `markdown` (the default) leaves bodies as they are,
+/// `html` adds a rendered `bodyHtml`.
+pub fn parse_render(value: Option<&str>) -> Result<bool, String> {
+    match value.map(str::trim) {
+        None | Some("") | Some("markdown") => Ok(false),
+        Some("html") => Ok(true),
+        Some(other) => Err(format!(
+            "Invalid render '{}', expected markdown or html",
+            other
+        )),
+    }
+}
@@ -0,0 +1,61 @@
+use pulldown_cmark::{Event, Parser, Tag, TagEnd, TextMergeStream};
+
+/// Most distinct people one comment can mention.
+pub const MAX_MENTIONS: usize = 20;
+
+/// Usernames mentioned as `@username` in a Markdown text, in order of first appearance and
+/// without duplicates. Mentions inside code spans and blocks don't count, and neither do email
+/// addresses, since the `@` has to start a word.
+pub fn parse_mentions(source: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    // Merged, because the parser may split a run of text in the middle of a username
+    for event in TextMergeStream::new(Parser::new(source)) {
+        let text = match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                continue;
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                continue;
+            }
+            Event::Text(text) if !in_code_block => text,
+            _ => continue,
+        };
+
+        let mut previous: Option<char> = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let starts_word = previous.is_none_or(|p| !is_username_char(p));
+            previous = Some(c);
+            if c != '@' || !starts_word {
+                continue;
+            }
+
+            let start = index + 1;
+            let mut end = start;
+            while let Some(&(next_index, next)) = chars.peek() {
+                if !is_username_char(next) {
+                    break;
+                }
+                end = next_index + next.len_utf8();
+                previous = Some(next);
+                chars.next();
+            }
+
+            // A mention at the end of a sentence doesn't take the full stop with it
+            let username = text[start..end].trim_end_matches(['.', '-']);
+            if !username.is_empty() && !mentions.iter().any(|m| m == username) {
+                mentions.push(username.to_string());
+            }
+        }
+    }
+
+    mentions
+}
+
+fn is_username_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
@@ -2,11 +2,34 @@ mod password_util;
 mod token_util;
 mod serializer_util;
 mod email_sender_util;
+mod email_template_util;
 mod otp_util;
 mod uploader_util;
+mod list_query_util;
+mod cursor_util;
+mod etag_util;
+mod list_text_util;
+mod markdown_util;
+mod signed_token_util;
+mod mention_util;
+mod webhook_util;
 
 pub use password_util::{hash_password, verify_password};
 pub use token_util::{decode_jwt, encode_jwt};
-pub use email_sender_util::{send_otp_mail};
+pub use email_sender_util::{
+    send_assignment_mail, send_digest_mail, send_due_soon_mail, send_invitation_mail,
+    send_mention_mail, send_otp_mail, send_reminder_mail, send_share_mail,
+};
+pub use email_template_util::{DigestEmail, DigestItem, DigestSection};
 pub use otp_util::{ generate_otp, check_otp_expiry};
-pub use uploader_util:: {upload_to_cloud};
\ No newline at end of file
+pub use uploader_util:: {upload_to_cloud};
+pub use list_query_util::{CursorDirection, ListQuery};
+pub use cursor_util::{decode_cursor, encode_cursor};
+pub use etag_util::{if_match_versions, list_etag};
+pub use list_text_util::{list_constraint_message, validate_list_text};
+pub use markdown_util::{parse_render, render_markdown};
+pub use mention_util::{parse_mentions, MAX_MENTIONS};
+pub use signed_token_util::{sign_token, verify_token};
+pub use webhook_util::{
+    check_webhook_url, generate_webhook_secret, resolve_webhook_url, sign_webhook,
+};
\ No newline at end of file
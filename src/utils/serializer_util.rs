@@ -1,4 +1,7 @@
-use crate::{models::UserModel, schemas::UserResponse};
+use crate::{
+    models::{ListModel, UserModel},
+    schemas::{ListResponse, UserResponse},
+};
 
 impl From<UserModel> for UserResponse{
     fn from(value: UserModel) -> Self {
@@ -12,4 +15,27 @@ impl From<UserModel> for UserResponse{
             updated_at: value.updated_at,
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<ListModel> for ListResponse {
+    fn from(value: ListModel) -> Self {
+        ListResponse {
+            id: value.id,
+            title: value.title,
+            descr: value.descr,
+            body: value.body,
+            user_id: value.user_id,
+            importance: value.importance,
+            position: value.position,
+            version: value.version,
+            status: value.status,
+            project_id: value.project_id,
+            workspace_id: value.workspace_id,
+            due_at: value.due_at,
+            completed_at: value.completed_at,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            tags: value.tags,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::future::Future;
 
-use anyhow::{Error, Result};
+use anyhow::Result;
 use cloudinary::upload::{result::UploadResult, Source, Upload, UploadOptions};
 use tempfile::TempPath;
 
@@ -18,8 +18,7 @@ pub fn upload_to_cloud<'a>(name: &'a str, path: &'a TempPath)->  impl Future<Out
     let upload = Upload::new(cloud_configs().2.to_string(), cloud_configs().0.to_string(), cloud_configs().1.to_string());
 
     async move{
-        let result = upload.image(Source::Path(path.into()), &options).await;
-        result.map_err(Error::from)
+        upload.image(Source::Path(path.into()), &options).await
     }
 }
 
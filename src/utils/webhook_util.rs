@@ -0,0 +1,163 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use reqwest::Url;
+use sha2::Sha256;
+
+/// Longest a webhook host may take to resolve. The request timeout only starts afterwards.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Random secret a webhook's payloads are signed with.
+pub fn generate_webhook_secret() -> String {
+    let secret: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    format!("whsec_{secret}")
+}
+
+/// Hex HMAC-SHA256 of `<timestamp>.<body>`. Covering the timestamp lets receivers reject
+/// replayed deliveries.
+pub fn sign_webhook(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Only http(s) URLs are accepted, and unless `allow_private` is set, none whose host resolves
+/// to a loopback, private or link-local address. Checked on registration and again before
+/// every delivery, since DNS can change in between.
+pub async fn check_webhook_url(url: &str, allow_private: bool) -> Result<Url, String> {
+    resolve_webhook_url(url, allow_private)
+        .await
+        .map(|(url, _)| url)
+}
+
+/// Same check as [`check_webhook_url`], also returning the addresses it approved. Deliveries
+/// connect to these rather than looking the host up again, which could answer differently.
+/// Empty when `allow_private` is set, as nothing is looked up then.
+pub async fn resolve_webhook_url(
+    url: &str,
+    allow_private: bool,
+) -> Result<(Url, Vec<SocketAddr>), String> {
+    let url = Url::parse(url).map_err(|_| "Webhook URL is not a valid URL".to_string())?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Webhook URL must use http or https".to_string());
+    }
+    let host = match url.host_str() {
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string(),
+        None => return Err("Webhook URL needs a host".to_string()),
+    };
+    if allow_private {
+        return Ok((url, Vec::new()));
+    }
+
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = tokio::time::timeout(
+        LOOKUP_TIMEOUT,
+        tokio::net::lookup_host((host.as_str(), port)),
+    )
+    .await
+    .map_err(|_| format!("Timed out resolving {}", host))?
+    .map_err(|_| format!("Cannot resolve {}", host))?
+    .collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public(&addr.ip())) {
+        return Err(format!("{} is not a public address", host));
+    }
+
+    Ok((url, addrs))
+}
+
+fn is_public(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => {
+            let octets = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                // "This network", 0.0.0.0/8
+                || octets[0] == 0
+                // Reserved, 240.0.0.0/4, which takes in the broadcast address
+                || octets[0] >= 240
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+        }
+        // Covers both the mapped (::ffff:a.b.c.d) and the compatible (::a.b.c.d) forms, and
+        // with them :: and ::1
+        IpAddr::V6(v6) => match v6.to_ipv4() {
+            Some(v4) => is_public(&IpAddr::V4(v4)),
+            None => {
+                let first = v6.segments()[0];
+                !(v6.is_multicast()
+                    // Unique local, fc00::/7, and link-local, fe80::/10
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(addr: &str) -> bool {
+        is_public(&addr.parse().unwrap())
+    }
+
+    #[test]
+    fn rejects_non_public_addresses() {
+        for addr in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "0.1.2.3",
+            "224.0.0.1",
+            "239.255.255.250",
+            "240.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "::127.0.0.1",
+            "::ffff:10.0.0.1",
+            "fc00::1",
+            "fe80::1",
+            "ff02::1",
+        ] {
+            assert!(!public(addr), "{addr} should not be public");
+        }
+    }
+
+    #[test]
+    fn accepts_public_addresses() {
+        for addr in [
+            "93.184.216.34",
+            "1.1.1.1",
+            "::ffff:8.8.8.8",
+            "2606:4700::1111",
+        ] {
+            assert!(public(addr), "{addr} should be public");
+        }
+    }
+}